@@ -32,24 +32,57 @@ export function getProgram(provider: AnchorProvider): BettingProgram {
 }
 
 /**
- * Helper to calculate current epoch ID
+ * Grid fields of a Market account (BN or number, as fetched)
  */
-export function getCurrentEpochId(epochLengthSecs: number = 300): number {
+export interface MarketGrid {
+  gridOriginTs: anchor.BN | number;
+  gridOriginEpochId: anchor.BN | number;
+  epochLengthSecs: number;
+  prevEpochLengthSecs: number;
+  cutoffSecs: number;
+}
+
+const toNumber = (value: anchor.BN | number) =>
+  typeof value === "number" ? value : value.toNumber();
+
+/**
+ * Epoch ID of the market's grid slot containing `ts` (mirrors Market::epoch_id_at)
+ * The asset's grid offset is already folded into the market's grid origin
+ */
+export function getEpochIdAt(market: MarketGrid, ts: number): number {
+  const originTs = toNumber(market.gridOriginTs);
+  const originEpochId = toNumber(market.gridOriginEpochId);
+  const offset = ts - originTs;
+  if (offset >= 0) {
+    return originEpochId + Math.floor(offset / market.epochLengthSecs);
+  }
+  // Slots before the origin keep the previous length
+  const slotsBack = Math.floor((-offset - 1) / market.prevEpochLengthSecs) + 1;
+  return Math.max(0, originEpochId - slotsBack);
+}
+
+/**
+ * Helper to calculate the market's current epoch ID
+ */
+export function getCurrentEpochId(market: MarketGrid): number {
   const now = Math.floor(Date.now() / 1000);
-  return Math.floor(now / epochLengthSecs);
+  return getEpochIdAt(market, now);
 }
 
 /**
- * Helper to calculate epoch timestamps
+ * Helper to calculate epoch timestamps on the market's grid (mirrors Market::epoch_start_ts)
  */
-export function getEpochTimestamps(
-  epochId: number,
-  epochLengthSecs: number = 300,
-  cutoffSecs: number = 30
-) {
-  const startTs = epochId * epochLengthSecs;
-  const endTs = startTs + epochLengthSecs;
-  const cutoffTs = endTs - cutoffSecs;
+export function getEpochTimestamps(market: MarketGrid, epochId: number) {
+  const originTs = toNumber(market.gridOriginTs);
+  const originEpochId = toNumber(market.gridOriginEpochId);
+  const startTs =
+    epochId >= originEpochId
+      ? originTs + (epochId - originEpochId) * market.epochLengthSecs
+      : originTs - (originEpochId - epochId) * market.prevEpochLengthSecs;
+  const lengthSecs =
+    epochId >= originEpochId ? market.epochLengthSecs : market.prevEpochLengthSecs;
+  const endTs = startTs + lengthSecs;
+  const cutoffTs = endTs - market.cutoffSecs;
 
   return {
     startTs,
@@ -81,11 +114,26 @@ export function getAssetConfigPda(
   );
 }
 
+/**
+ * Derive market PDA
+ */
+export function getMarketPda(
+  assetSymbol: string,
+  timeframe: string,
+  programId: PublicKey = PROGRAM_ID
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("market"), Buffer.from(assetSymbol), Buffer.from(timeframe)],
+    programId
+  );
+}
+
 /**
  * Derive epoch PDA
  */
 export function getEpochPda(
   assetSymbol: string,
+  timeframe: string,
   epochId: number,
   programId: PublicKey = PROGRAM_ID
 ): [PublicKey, number] {
@@ -93,7 +141,7 @@ export function getEpochPda(
   epochIdBe.writeBigUInt64BE(BigInt(epochId));
 
   return PublicKey.findProgramAddressSync(
-    [Buffer.from("epoch"), Buffer.from(assetSymbol), epochIdBe],
+    [Buffer.from("epoch"), Buffer.from(assetSymbol), Buffer.from(timeframe), epochIdBe],
     programId
   );
 }
@@ -118,6 +166,7 @@ export function getVaultPda(
 export function getUserBetPda(
   user: PublicKey,
   assetSymbol: string,
+  timeframe: string,
   epochId: number,
   programId: PublicKey = PROGRAM_ID
 ): [PublicKey, number] {
@@ -125,7 +174,13 @@ export function getUserBetPda(
   epochIdBe.writeBigUInt64BE(BigInt(epochId));
 
   return PublicKey.findProgramAddressSync(
-    [Buffer.from("bet"), user.toBuffer(), Buffer.from(assetSymbol), epochIdBe],
+    [
+      Buffer.from("bet"),
+      user.toBuffer(),
+      Buffer.from(assetSymbol),
+      Buffer.from(timeframe),
+      epochIdBe,
+    ],
     programId
   );
 }
@@ -45,5 +45,6 @@ export const ASSETS: AssetConfig[] = [
 ];
 
 // Epoch settings
+export const TIMEFRAME = process.env.NEXT_PUBLIC_TIMEFRAME || "5m"; // market timeframe shown in the app
 export const EPOCH_LENGTH_SECS = 300; // 5 minutes
 export const CUTOFF_SECS = 30; // 30 seconds before end
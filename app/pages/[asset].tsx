@@ -13,6 +13,7 @@ import {
   getCurrentEpochId,
   getEpochTimestamps,
   getEpochPda,
+  getMarketPda,
   getAssetConfigPda,
   getConfigPda,
   getVaultPda,
@@ -21,7 +22,7 @@ import {
   formatUsdc,
   parseUsdc,
 } from "../lib/anchor";
-import { ASSETS, USDC_MINT } from "../lib/config";
+import { ASSETS, TIMEFRAME, USDC_MINT } from "../lib/config";
 
 export default function AssetPage() {
   const router = useRouter();
@@ -39,16 +40,29 @@ export default function AssetPage() {
 
   const asset = ASSETS.find((a) => a.symbol === assetSymbol);
 
-  // Calculate current epoch ID and timestamps
+  // Calculate current epoch ID and timestamps from the market's grid
   useEffect(() => {
-    if (!assetSymbol) return;
+    if (!assetSymbol || !wallet.publicKey) return;
+
+    const loadMarket = async () => {
+      try {
+        const provider = getProvider(wallet as any);
+        const program = getProgram(provider);
 
-    const currentEpochId = getCurrentEpochId();
-    setEpochId(currentEpochId);
+        const [marketPda] = getMarketPda(assetSymbol as string, TIMEFRAME);
+        const market = await (program.account as any).market.fetch(marketPda);
+
+        const currentEpochId = getCurrentEpochId(market);
+        setEpochId(currentEpochId);
+        setTimestamps(getEpochTimestamps(market, currentEpochId));
+      } catch (err) {
+        console.error("Failed to load market:", err);
+        setLoading(false);
+      }
+    };
 
-    const ts = getEpochTimestamps(currentEpochId);
-    setTimestamps(ts);
-  }, [assetSymbol]);
+    loadMarket();
+  }, [assetSymbol, wallet.publicKey]);
 
   // Load epoch data
   useEffect(() => {
@@ -59,7 +73,7 @@ export default function AssetPage() {
         const provider = getProvider(wallet as any);
         const program = getProgram(provider);
 
-        const [epochPda] = getEpochPda(assetSymbol as string, epochId);
+        const [epochPda] = getEpochPda(assetSymbol as string, TIMEFRAME, epochId);
 
         // Check if program has account methods (will be undefined with placeholder IDL)
         if (program.account && (program.account as any).epoch) {
@@ -107,13 +121,14 @@ export default function AssetPage() {
 
       const amount = parseUsdc(parseFloat(betAmount));
 
-      const [epochPda] = getEpochPda(assetSymbol as string, epochId);
+      const [epochPda] = getEpochPda(assetSymbol as string, TIMEFRAME, epochId);
       const [assetConfigPda] = getAssetConfigPda(assetSymbol as string);
       const [configPda] = getConfigPda();
       const [vaultPda] = getVaultPda(USDC_MINT, assetSymbol as string);
       const [userBetPda] = getUserBetPda(
         wallet.publicKey,
         assetSymbol as string,
+        TIMEFRAME,
         epochId
       );
 
@@ -127,6 +142,7 @@ export default function AssetPage() {
       const tx = await (program as any).methods
         .placeBet(
           assetSymbol,
+          TIMEFRAME,
           new anchor.BN(epochId),
           betSide,
           new anchor.BN(amount)
@@ -55,4 +55,115 @@ pub enum BettingError {
 
     #[msg("Pyth price account mismatch")]
     PythAccountMismatch,
+
+    #[msg("Epoch is not refundable")]
+    NotRefundable,
+
+    #[msg("Epoch is refundable; use refund instead of claim")]
+    UseRefund,
+
+    #[msg("Invalid oracle guard value")]
+    InvalidOracleGuard,
+
+    #[msg("An account for a configured oracle source is missing")]
+    OracleAccountMissing,
+
+    #[msg("Too many secondary oracle sources")]
+    TooManyOracles,
+
+    #[msg("Fee exceeds the maximum allowed")]
+    FeeTooHigh,
+
+    #[msg("Epoch length must be greater than zero")]
+    InvalidEpochLength,
+
+    #[msg("Cutoff must be shorter than the epoch length")]
+    InvalidCutoff,
+
+    #[msg("Signer is not the program upgrade authority")]
+    NotUpgradeAuthority,
+
+    #[msg("No admin transfer is pending for this signer")]
+    NotPendingAdmin,
+
+    #[msg("This change must be queued behind the timelock")]
+    TimelockRequired,
+
+    #[msg("Timelock has not elapsed for this change")]
+    TimelockNotElapsed,
+
+    #[msg("Asset config does not match the queued change")]
+    ChangeAssetMismatch,
+
+    #[msg("Mint does not use the expected USDC decimals")]
+    InvalidMintDecimals,
+
+    #[msg("Fee account is not the treasury's associated token account")]
+    InvalidTreasuryAccount,
+
+    #[msg("Keeper fee share exceeds 100% of the fee")]
+    KeeperFeeTooHigh,
+
+    #[msg("Active epoch is the grid epoch; omit active_epoch")]
+    DuplicateEpochAccount,
+
+    #[msg("Epoch is not within the pre-creation window")]
+    EpochOutOfRange,
+
+    #[msg("Epoch has already started")]
+    EpochAlreadyStarted,
+
+    #[msg("Timeframe label too long")]
+    TimeframeTooLong,
+
+    #[msg("Market does not match the queued change")]
+    ChangeMarketMismatch,
+
+    #[msg("Bet account uses the old single-sided layout; call migrate_bet first")]
+    BetNeedsMigration,
+
+    #[msg("Bet is below the asset's minimum bet")]
+    BetTooSmall,
+
+    #[msg("Bet is above the asset's maximum bet")]
+    BetTooLarge,
+
+    #[msg("Bet would exceed the per-user stake cap for this epoch")]
+    UserStakeLimitExceeded,
+
+    #[msg("Bet would exceed the epoch pool cap")]
+    EpochPoolFull,
+
+    #[msg("Minimum bet is above the maximum bet")]
+    InvalidBetLimits,
+
+    #[msg("Remaining accounts must be (epoch, user_bet) pairs")]
+    InvalidClaimBatch,
+
+    #[msg("Timeframe label is empty; the empty timeframe is reserved for migrated epochs")]
+    EmptyTimeframe,
+
+    #[msg("Asset already has markets; its grid offset can no longer change")]
+    GridOffsetLocked,
+
+    #[msg("Epochs are pre-created past the current slot; the epoch length can change once they have started")]
+    EpochsPreCreated,
+
+    #[msg("Epoch timestamps do not match the market's current grid slot")]
+    EpochGridMismatch,
+
+    #[msg("More than one account was passed for the same oracle source")]
+    DuplicateOracleAccount,
+
+    #[msg("Oracle sources must be distinct from each other and from the primary feed")]
+    DuplicateOracleSource,
+
+    #[msg("Vault account is not the asset's vault")]
+    InvalidVault,
+
+    #[msg("The market's active epoch must be passed before the next epoch can take over")]
+    ActiveEpochRequired,
+
+    #[msg("Asset already has a vault; its mint can no longer change")]
+    MintLocked,
 }
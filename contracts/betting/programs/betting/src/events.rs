@@ -1,21 +1,38 @@
 use anchor_lang::prelude::*;
-use crate::state::{BetSide, WinningSide};
+use crate::state::{BetSide, WinningSide, GovernanceChange};
+use crate::oracle::{OracleError, OracleSource};
 
 /// Emitted when a user places a bet
 #[event]
 pub struct BetPlaced {
     pub user: Pubkey,
     pub asset_symbol: String,
+    pub timeframe: String,
     pub epoch_id: u64,
     pub side: BetSide,
     pub amount: u64,
     pub timestamp: i64,
 }
 
+/// Emitted when a user adds to their existing position (either side)
+#[event]
+pub struct BetIncreased {
+    pub user: Pubkey,
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_id: u64,
+    pub side: BetSide,
+    pub amount: u64,
+    /// User's total stake on `side` after the top-up
+    pub stake: u64,
+    pub timestamp: i64,
+}
+
 /// Emitted when an epoch is locked (betting closed)
 #[event]
 pub struct EpochLocked {
     pub asset_symbol: String,
+    pub timeframe: String,
     pub epoch_id: u64,
     pub timestamp: i64,
     pub sum_up: u64,
@@ -26,12 +43,20 @@ pub struct EpochLocked {
 #[event]
 pub struct EpochSettled {
     pub asset_symbol: String,
+    pub timeframe: String,
     pub epoch_id: u64,
+    pub start_price: i64,
+    pub start_expo: i32,
     pub settle_price: i64,
     pub settle_expo: i32,
     pub winning_side: WinningSide,
+    /// Total fee (treasury_fee + keeper_fee)
     pub fee_amount: u64,
+    pub treasury_fee: u64,
+    pub keeper_fee: u64,
+    pub keeper: Pubkey,
     pub net_pool: u64,
+    pub rollover_out: u64,
     pub timestamp: i64,
 }
 
@@ -40,17 +65,200 @@ pub struct EpochSettled {
 pub struct Claimed {
     pub user: Pubkey,
     pub asset_symbol: String,
+    pub timeframe: String,
     pub epoch_id: u64,
     pub payout: u64,
     pub timestamp: i64,
 }
 
+/// Emitted once per claim_many with the combined payout
+#[event]
+pub struct ClaimedBatch {
+    pub user: Pubkey,
+    pub asset_symbol: String,
+    /// Pairs that paid out
+    pub claimed: u32,
+    /// Losing, already-claimed or not yet claimable pairs
+    pub skipped: u32,
+    pub payout: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a user is refunded their stake
+#[event]
+pub struct Refunded {
+    pub user: Pubkey,
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_id: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a user cancels a bet before cutoff
+#[event]
+pub struct BetCancelled {
+    pub user: Pubkey,
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_id: u64,
+    pub stake_up: u64,
+    pub stake_down: u64,
+    /// Stake returned to the user
+    pub refund: u64,
+    /// Cancellation fee sent to the treasury
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
 /// Emitted when a new epoch is created
 #[event]
 pub struct EpochCreated {
     pub asset_symbol: String,
+    pub timeframe: String,
     pub epoch_id: u64,
     pub start_ts: i64,
     pub cutoff_ts: i64,
     pub end_ts: i64,
+    pub start_price: i64,
+    pub start_expo: i32,
+}
+
+/// Emitted when a pre-created epoch becomes the market's active epoch
+#[event]
+pub struct EpochStarted {
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_id: u64,
+    /// Zero when no price near start_ts was available yet (captured at settlement instead)
+    pub start_price: i64,
+    pub start_expo: i32,
+    pub rollover_in: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when an epoch cannot be settled and is marked Invalid
+#[event]
+pub struct EpochInvalidated {
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_id: u64,
+    pub reason: OracleError,
+    pub timestamp: i64,
+}
+
+/// Emitted when an oracle source is rejected or outvoted during quorum
+#[event]
+pub struct OracleOutlier {
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_id: u64,
+    /// Position in the asset's oracle sources (0 = primary)
+    pub source_index: u8,
+    pub source: OracleSource,
+    pub deviation_bps: u64,
+    pub reason: OracleError,
+    pub timestamp: i64,
+}
+
+/// Emitted when the admin changes the global configuration
+#[event]
+pub struct ConfigUpdated {
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub old_settle_tip_lamports: u64,
+    pub new_settle_tip_lamports: u64,
+    pub old_cutoff_secs: u32,
+    pub new_cutoff_secs: u32,
+    pub old_epoch_length_secs: u32,
+    pub new_epoch_length_secs: u32,
+    pub old_treasury: Pubkey,
+    pub new_treasury: Pubkey,
+    pub old_timelock_secs: u32,
+    pub new_timelock_secs: u32,
+    pub old_keeper_fee_bps: u16,
+    pub new_keeper_fee_bps: u16,
+    pub old_max_future_epochs: u16,
+    pub new_max_future_epochs: u16,
+    pub old_cancel_fee_bps: u16,
+    pub new_cancel_fee_bps: u16,
+    pub timestamp: i64,
+}
+
+/// Emitted when a market is created or its timing or fee override changes
+#[event]
+pub struct MarketUpdated {
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_length_secs: u32,
+    pub cutoff_secs: u32,
+    /// None when the market follows the global fee
+    pub fee_bps: Option<u16>,
+    /// First timestamp at which epoch_length_secs applies
+    pub grid_origin_ts: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the admin proposes a successor
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the proposed admin accepts the role
+#[event]
+pub struct AdminTransferred {
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when the admin reassigns operational roles
+#[event]
+pub struct RolesUpdated {
+    pub pauser: Pubkey,
+    pub fee_manager: Pubkey,
+    pub asset_manager: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Emitted when a sensitive change is queued behind the timelock
+#[event]
+pub struct ChangeQueued {
+    pub change_id: u64,
+    pub change: GovernanceChange,
+    pub proposer: Pubkey,
+    /// Earliest time the change can be executed
+    pub eta: i64,
+    pub timestamp: i64,
+}
+
+/// Emitted when a queued change is applied
+#[event]
+pub struct ChangeExecuted {
+    pub change_id: u64,
+    pub change: GovernanceChange,
+    pub timestamp: i64,
+}
+
+/// Emitted when the admin cancels a queued change
+#[event]
+pub struct ChangeCancelled {
+    pub change_id: u64,
+    pub timestamp: i64,
+}
+
+/// Emitted when the tip jar cannot cover the settle tip
+#[event]
+pub struct TipSkipped {
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_id: u64,
+    pub caller: Pubkey,
+    pub tip_lamports: u64,
+    /// Lamports in the jar above its rent-exempt minimum
+    pub available_lamports: u64,
+    pub timestamp: i64,
 }
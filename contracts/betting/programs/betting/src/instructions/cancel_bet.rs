@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_spl::associated_token::get_associated_token_address;
+use crate::state::{GlobalConfig, AssetConfig, Epoch, UserBet, EpochStatus};
+use crate::errors::BettingError;
+use crate::events::BetCancelled;
+
+/// Cancel a bet before cutoff
+/// Both sides of the position come off the pool; the stake minus the cancellation fee is returned
+/// The bet account is closed so the user also gets its rent back
+pub fn cancel_bet(
+    ctx: Context<CancelBet>,
+    asset_symbol: String,
+    timeframe: String,
+    epoch_id: u64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let epoch = &mut ctx.accounts.epoch;
+    let user_bet = &ctx.accounts.user_bet;
+
+    // Check epoch status
+    require!(
+        epoch.status == EpochStatus::Open,
+        BettingError::InvalidEpochStatus
+    );
+
+    // Check we haven't reached cutoff time
+    let clock = Clock::get()?;
+    require!(
+        clock.unix_timestamp < epoch.cutoff_ts,
+        BettingError::BettingClosed
+    );
+
+    // Take the position out of the epoch pool
+    epoch.sum_up = epoch.sum_up
+        .checked_sub(user_bet.stake_up)
+        .ok_or(BettingError::Overflow)?;
+    epoch.sum_down = epoch.sum_down
+        .checked_sub(user_bet.stake_down)
+        .ok_or(BettingError::Overflow)?;
+
+    let stake = user_bet.total_stake()?;
+    let fee = config.cancel_fee(stake);
+    let refund = stake - fee;
+
+    let seeds = &[
+        b"vault",
+        ctx.accounts.asset_config.usdc_mint.as_ref(),
+        asset_symbol.as_bytes(),
+        &[ctx.bumps.vault],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    for (to, amount) in [
+        (ctx.accounts.user_ata.to_account_info(), refund),
+        (ctx.accounts.treasury_ata.to_account_info(), fee),
+    ] {
+        if amount == 0 {
+            continue;
+        }
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to,
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, amount)?;
+    }
+
+    emit!(BetCancelled {
+        user: ctx.accounts.user.key(),
+        asset_symbol,
+        timeframe,
+        epoch_id,
+        stake_up: user_bet.stake_up,
+        stake_down: user_bet.stake_down,
+        refund,
+        fee,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Bet cancelled: refunded {} USDC (fee {})", refund, fee);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String, timeframe: String, epoch_id: u64)]
+pub struct CancelBet<'info> {
+    /// Closed to the user once the stake is returned
+    #[account(
+        mut,
+        close = user,
+        seeds = [
+            b"bet",
+            user.key().as_ref(),
+            asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
+            &epoch_id.to_be_bytes()
+        ],
+        bump = user_bet.bump,
+        has_one = user @ BettingError::Unauthorized,
+        constraint = user_bet.to_account_info().data_len() == UserBet::LEN
+            @ BettingError::BetNeedsMigration,
+    )]
+    pub user_bet: Account<'info, UserBet>,
+
+    #[account(
+        mut,
+        seeds = [
+            b"epoch",
+            asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
+            &epoch_id.to_be_bytes()
+        ],
+        bump = epoch.bump,
+    )]
+    pub epoch: Account<'info, Epoch>,
+
+    #[account(
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Vault token account holding USDC for this asset
+    #[account(
+        mut,
+        seeds = [b"vault", asset_config.usdc_mint.as_ref(), asset_symbol.as_bytes()],
+        bump,
+        address = asset_config.vault @ BettingError::InvalidVault,
+        token::mint = asset_config.usdc_mint,
+        token::authority = vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// Treasury's associated token account to receive the cancellation fee
+    #[account(
+        mut,
+        address = get_associated_token_address(&config.treasury, &asset_config.usdc_mint)
+            @ BettingError::InvalidTreasuryAccount,
+    )]
+    pub treasury_ata: Account<'info, TokenAccount>,
+
+    /// User's USDC token account
+    #[account(
+        mut,
+        token::mint = asset_config.usdc_mint,
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
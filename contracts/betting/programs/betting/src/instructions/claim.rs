@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::state::{GlobalConfig, AssetConfig, Epoch, UserBet, EpochStatus, WinningSide, BetSide};
+use crate::state::{GlobalConfig, AssetConfig, Epoch, UserBet, EpochStatus};
 use crate::errors::BettingError;
 use crate::events::Claimed;
 
@@ -8,9 +8,9 @@ use crate::events::Claimed;
 pub fn claim(
     ctx: Context<Claim>,
     asset_symbol: String,
+    timeframe: String,
     epoch_id: u64,
 ) -> Result<()> {
-    let config = &ctx.accounts.config;
     let epoch = &ctx.accounts.epoch;
     let user_bet = &mut ctx.accounts.user_bet;
 
@@ -20,20 +20,18 @@ pub fn claim(
         BettingError::InvalidEpochStatus
     );
 
+    // Ties, empty sides and invalid epochs go through refund
+    require!(!epoch.is_refundable(), BettingError::UseRefund);
+
     // Check user hasn't already claimed
     require!(!user_bet.claimed, BettingError::AlreadyClaimed);
 
-    // Check user is a winner
-    let is_winner = match (epoch.winning_side.clone(), user_bet.side) {
-        (WinningSide::Up, BetSide::Up) => true,
-        (WinningSide::Down, BetSide::Down) => true,
-        _ => false,
-    };
-
-    require!(is_winner, BettingError::NotWinner);
+    // Only stake on the winning side is paid; a hedged position's losing stake stays in the pool
+    let winning_stake = user_bet.winning_stake(&epoch.winning_side);
+    require!(winning_stake > 0, BettingError::NotWinner);
 
     // Calculate payout
-    let payout = epoch.calculate_payout(user_bet.stake, config.fee_bps)?;
+    let payout = epoch.calculate_payout(winning_stake, epoch.fee_bps)?;
 
     require!(payout > 0, BettingError::ZeroWinningPool);
 
@@ -64,6 +62,7 @@ pub fn claim(
     emit!(Claimed {
         user: ctx.accounts.user.key(),
         asset_symbol,
+        timeframe,
         epoch_id,
         payout,
         timestamp: clock.unix_timestamp,
@@ -75,7 +74,7 @@ pub fn claim(
 }
 
 #[derive(Accounts)]
-#[instruction(asset_symbol: String, epoch_id: u64)]
+#[instruction(asset_symbol: String, timeframe: String, epoch_id: u64)]
 pub struct Claim<'info> {
     #[account(
         mut,
@@ -83,10 +82,13 @@ pub struct Claim<'info> {
             b"bet",
             user.key().as_ref(),
             asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
             &epoch_id.to_be_bytes()
         ],
         bump = user_bet.bump,
         has_one = user @ BettingError::Unauthorized,
+        constraint = user_bet.to_account_info().data_len() == UserBet::LEN
+            @ BettingError::BetNeedsMigration,
     )]
     pub user_bet: Account<'info, UserBet>,
 
@@ -94,6 +96,7 @@ pub struct Claim<'info> {
         seeds = [
             b"epoch",
             asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
             &epoch_id.to_be_bytes()
         ],
         bump = epoch.bump,
@@ -117,6 +120,7 @@ pub struct Claim<'info> {
         mut,
         seeds = [b"vault", asset_config.usdc_mint.as_ref(), asset_symbol.as_bytes()],
         bump,
+        address = asset_config.vault @ BettingError::InvalidVault,
         token::mint = asset_config.usdc_mint,
         token::authority = vault,
     )]
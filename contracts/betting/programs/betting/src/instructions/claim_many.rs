@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use anchor_lang::error::ErrorCode;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{GlobalConfig, AssetConfig, Epoch, UserBet, EpochStatus};
+use crate::errors::BettingError;
+use crate::events::ClaimedBatch;
+
+/// Claim winnings for many settled epochs of one asset in one transaction
+/// Remaining accounts are (epoch, user_bet) pairs, across any of the asset's timeframes
+/// Pairs that lost, were already claimed or are not claimable are skipped; payouts go out in one transfer
+pub fn claim_many<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimMany<'info>>,
+    asset_symbol: String,
+) -> Result<()> {
+    let pairs = ctx.remaining_accounts.chunks_exact(2);
+    require!(
+        pairs.len() > 0 && pairs.remainder().is_empty(),
+        BettingError::InvalidClaimBatch
+    );
+
+    let user = ctx.accounts.user.key();
+    let mut payout: u64 = 0;
+    let mut claimed: u32 = 0;
+    let mut skipped: u32 = 0;
+
+    for pair in pairs {
+        let epoch = Account::<Epoch>::try_from(&pair[0])?;
+        require!(
+            epoch.asset_symbol == asset_symbol,
+            ErrorCode::ConstraintSeeds
+        );
+        let epoch_id = epoch.epoch_id.to_be_bytes();
+        let epoch_pda = Pubkey::create_program_address(
+            &[
+                b"epoch",
+                asset_symbol.as_bytes(),
+                epoch.timeframe.as_bytes(),
+                &epoch_id,
+                &[epoch.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(epoch.key(), epoch_pda, ErrorCode::ConstraintSeeds);
+
+        require!(pair[1].is_writable, ErrorCode::ConstraintMut);
+        require!(
+            pair[1].data_len() == UserBet::LEN,
+            BettingError::BetNeedsMigration
+        );
+        let mut user_bet = Account::<UserBet>::try_from(&pair[1])?;
+        require_keys_eq!(user_bet.user, user, BettingError::Unauthorized);
+        let bet_pda = Pubkey::create_program_address(
+            &[
+                b"bet",
+                user.as_ref(),
+                asset_symbol.as_bytes(),
+                epoch.timeframe.as_bytes(),
+                &epoch_id,
+                &[user_bet.bump],
+            ],
+            &crate::ID,
+        )
+        .map_err(|_| ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(user_bet.key(), bet_pda, ErrorCode::ConstraintSeeds);
+
+        // Same rules as claim, but a pair that cannot be claimed is skipped instead of failing
+        let winning_stake = user_bet.winning_stake(&epoch.winning_side);
+        if epoch.status != EpochStatus::Settled
+            || epoch.is_refundable()
+            || user_bet.claimed
+            || winning_stake == 0
+        {
+            skipped += 1;
+            continue;
+        }
+
+        let amount = epoch.calculate_payout(winning_stake, epoch.fee_bps)?;
+        if amount == 0 {
+            skipped += 1;
+            continue;
+        }
+
+        // Written back straight away so a repeated pair reads as already claimed
+        user_bet.claimed = true;
+        user_bet.exit(&crate::ID)?;
+
+        payout = payout.checked_add(amount).ok_or(BettingError::Overflow)?;
+        claimed += 1;
+    }
+
+    if payout > 0 {
+        let seeds = &[
+            b"vault",
+            ctx.accounts.asset_config.usdc_mint.as_ref(),
+            asset_symbol.as_bytes(),
+            &[ctx.bumps.vault],
+        ];
+        let signer_seeds = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.vault.to_account_info(),
+                to: ctx.accounts.user_ata.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            signer_seeds,
+        );
+        token::transfer(transfer_ctx, payout)?;
+    }
+
+    let clock = Clock::get()?;
+    emit!(ClaimedBatch {
+        user,
+        asset_symbol,
+        claimed,
+        skipped,
+        payout,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Claimed {} USDC from {} epochs ({} skipped)", payout, claimed, skipped);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String)]
+pub struct ClaimMany<'info> {
+    #[account(
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Vault token account holding USDC for this asset
+    #[account(
+        mut,
+        seeds = [b"vault", asset_config.usdc_mint.as_ref(), asset_symbol.as_bytes()],
+        bump,
+        address = asset_config.vault @ BettingError::InvalidVault,
+        token::mint = asset_config.usdc_mint,
+        token::authority = vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// User's USDC token account
+    #[account(
+        mut,
+        token::mint = asset_config.usdc_mint,
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
@@ -1,19 +1,23 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::program::invoke;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
-use crate::state::{GlobalConfig, AssetConfig, Epoch, EpochStatus, WinningSide};
+use anchor_spl::associated_token::get_associated_token_address;
+use crate::state::{GlobalConfig, AssetConfig, Market, Epoch, EpochStatus, WinningSide, OneSidedPolicy};
 use crate::errors::BettingError;
-use crate::events::EpochSettled;
+use crate::events::{EpochSettled, EpochInvalidated, OracleOutlier};
+use super::pay_settle_tips;
+use crate::oracle::{self, OracleError, OracleKind};
+use crate::oracle::quorum::Outlier;
 
 /// Close and settle an epoch using Pyth price oracle
 /// Anyone can call this once end_ts is reached
-/// Caller receives a small tip for settling
-pub fn close_epoch(
-    ctx: Context<CloseEpoch>,
+/// Caller receives a small tip from the tip jar and the keeper share of the fee for settling
+/// (no tip when the oracle check invalidates the epoch)
+pub fn close_epoch<'info>(
+    ctx: Context<'_, '_, '_, 'info, CloseEpoch<'info>>,
     asset_symbol: String,
+    timeframe: String,
     epoch_id: u64,
 ) -> Result<()> {
-    let config = &ctx.accounts.config;
     let epoch = &mut ctx.accounts.epoch;
 
     // Check we've reached end time
@@ -31,136 +35,281 @@ pub fn close_epoch(
 
     // Read Pyth price account
     let pyth_price_account = &ctx.accounts.pyth_price_account;
+    require_primary_oracle(&ctx.accounts.asset_config, pyth_price_account)?;
 
-    // Verify this is the correct Pyth account for this asset
-    require!(
-        pyth_price_account.key() == ctx.accounts.asset_config.pyth_price_account,
-        BettingError::PythAccountMismatch
-    );
+    // Secondary oracle accounts and extra PriceUpdateV2 accounts are passed as remaining accounts
+    let mut candidates = vec![pyth_price_account.to_account_info()];
+    candidates.extend_from_slice(ctx.remaining_accounts);
 
-    // Parse Pyth price data
-    // NOTE: In production, use pyth-sdk-solana crate for proper parsing
-    // For now, we'll use a simplified approach
-    let price_data = pyth_price_account.try_borrow_data()?;
-
-    // Simple price extraction (in production, use proper Pyth SDK)
-    // This is a placeholder - you should use:
-    // use pyth_sdk_solana::load_price_feed_from_account_info;
-    // let price_feed = load_price_feed_from_account_info(&pyth_price_account)?;
-    // let price = price_feed.get_price_no_older_than(&clock, 60)?;
-
-    // For this example, we'll assume a simple price structure
-    // In reality, parse the Pyth account data properly
-    if price_data.len() < 32 {
-        epoch.status = EpochStatus::Invalid;
-        msg!("Invalid Pyth price data");
-        return Ok(());
-    }
+    let settled = settle_epoch(
+        epoch,
+        &mut ctx.accounts.market,
+        &ctx.accounts.asset_config,
+        &ctx.accounts.config,
+        &candidates,
+        FeeAccounts {
+            vault: &ctx.accounts.vault,
+            vault_bump: ctx.bumps.vault,
+            treasury_ata: &ctx.accounts.treasury_ata,
+            caller_ata: &ctx.accounts.caller_ata,
+            token_program: &ctx.accounts.token_program,
+        },
+        ctx.accounts.caller.key(),
+        clock.unix_timestamp,
+    )?;
 
-    // Placeholder: Read price from Pyth account (use proper Pyth SDK in production)
-    // This is a simplified extraction - MUST be replaced with actual Pyth SDK calls
-    let settle_price: i64 = i64::from_le_bytes(price_data[16..24].try_into().unwrap_or([0; 8]));
-    let settle_expo: i32 = i32::from_le_bytes(price_data[24..28].try_into().unwrap_or([0; 4]));
+    pay_settle_tips(
+        &ctx.accounts.tip_jar,
+        ctx.bumps.tip_jar,
+        &ctx.accounts.caller,
+        &ctx.accounts.system_program,
+        &ctx.accounts.config,
+        settle_tips(settled),
+        &asset_symbol,
+        &timeframe,
+        epoch_id,
+        clock.unix_timestamp,
+    )?;
 
-    // Validate price is reasonable (non-zero)
-    if settle_price == 0 {
-        epoch.status = EpochStatus::Invalid;
-        msg!("Price is zero, marking epoch as invalid");
-        return Ok(());
+    Ok(())
+}
+
+/// Settle tips earned by one settle_epoch call, shared by close_epoch and crank
+/// Invalidating an epoch earns nothing, so keepers gain nothing by steering epochs into refunds
+pub(crate) fn settle_tips(settled: bool) -> u64 {
+    u64::from(settled)
+}
+
+/// Legacy feeds are pinned to one account; pull updates are matched by feed id instead
+pub(crate) fn require_primary_oracle(
+    asset_config: &AssetConfig,
+    pyth_price_account: &AccountInfo,
+) -> Result<()> {
+    if asset_config.oracle_kind == OracleKind::PythLegacy {
+        require!(
+            pyth_price_account.key() == asset_config.pyth_price_account,
+            BettingError::PythAccountMismatch
+        );
     }
+    Ok(())
+}
+
+/// Token accounts an epoch fee is paid from and to
+pub(crate) struct FeeAccounts<'a, 'info> {
+    pub vault: &'a Account<'info, TokenAccount>,
+    pub vault_bump: u8,
+    pub treasury_ata: &'a Account<'info, TokenAccount>,
+    pub caller_ata: &'a Account<'info, TokenAccount>,
+    pub token_program: &'a Program<'info, Token>,
+}
+
+/// Settle an epoch that has reached end_ts against the asset's oracle sources
+/// Oracle failures mark the epoch Invalid and return false; missing oracle accounts revert
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn settle_epoch<'info>(
+    epoch: &mut Epoch,
+    market: &mut Market,
+    asset_config: &AssetConfig,
+    config: &GlobalConfig,
+    candidates: &[AccountInfo<'info>],
+    fees: FeeAccounts<'_, 'info>,
+    keeper: Pubkey,
+    now: i64,
+) -> Result<bool> {
+    let asset_symbol = epoch.asset_symbol.clone();
+    let timeframe = epoch.timeframe.clone();
+    let epoch_id = epoch.epoch_id;
+
+    // A pre-created epoch that started without a usable price reads its start price now
+    let start = if epoch.has_start_price() {
+        Ok(None)
+    } else {
+        oracle::quorum::read_quorum_price(candidates, &config.pyth_program, asset_config, epoch.start_ts)
+            .map(Some)
+    };
+
+    // Combine every source under the asset's quorum rule, each read against end_ts
+    // Any oracle failure or disagreement makes the epoch refundable
+    let quorum = start.and_then(|start| {
+        if let Some(start) = start {
+            for outlier in start.outliers {
+                emit_outlier(&asset_symbol, &timeframe, epoch_id, outlier, now);
+            }
+            epoch.start_price = start.price.price;
+            epoch.start_expo = start.price.expo;
+            epoch.start_publish_ts = start.price.publish_time;
+        }
+        oracle::quorum::read_quorum_price(candidates, &config.pyth_program, asset_config, epoch.end_ts)
+    });
+    let price = match quorum {
+        Ok(quorum) => {
+            for outlier in quorum.outliers {
+                emit_outlier(&asset_symbol, &timeframe, epoch_id, outlier, now);
+            }
+            quorum.price
+        }
+        Err(failure) if failure.reason == OracleError::MissingAccount => {
+            return err!(BettingError::OracleAccountMissing);
+        }
+        Err(failure) if failure.reason == OracleError::DuplicateAccount => {
+            return err!(BettingError::DuplicateOracleAccount);
+        }
+        Err(failure) => {
+            epoch.status = EpochStatus::Invalid;
+            // Stakes are refunded, so pass any carried-in pool on to the next epoch
+            market.pending_rollover = market.pending_rollover
+                .checked_add(epoch.rollover_in)
+                .ok_or(BettingError::Overflow)?;
+            if let Some(outlier) = failure.outlier {
+                emit_outlier(&asset_symbol, &timeframe, epoch_id, outlier, now);
+            }
+            emit!(EpochInvalidated {
+                asset_symbol,
+                timeframe,
+                epoch_id,
+                reason: failure.reason,
+                timestamp: now,
+            });
+            msg!("Invalid oracle price: {:?}, marking epoch as invalid", failure.reason);
+            return Ok(false);
+        }
+    };
+
+    let settle_price = price.price;
+    let settle_expo = price.expo;
 
     // Store settlement price
     epoch.settle_price = settle_price;
     epoch.settle_expo = settle_expo;
 
-    // TODO: Compare with price at epoch start to determine winner
-    // For now, simplified logic: if price is positive, Up wins, else Down wins
-    // In production, you need to:
-    // 1. Store or fetch the price at epoch.start_ts
-    // 2. Compare settle_price with start_price
-    // 3. Determine if price went Up or Down
+    // Compare against the start price captured at epoch open
+    epoch.winning_side = epoch.determine_winner()?;
+    epoch.status = EpochStatus::Settled;
 
-    // Placeholder logic - replace with actual price comparison
-    epoch.winning_side = if settle_price > 0 {
-        WinningSide::Up
+    // Nobody backed the winner: optionally roll the whole pool into the next epoch
+    if epoch.winning_side != WinningSide::None
+        && epoch.winning_pool() == 0
+        && asset_config.one_sided_policy == OneSidedPolicy::Rollover
+    {
+        epoch.rolled_over = true;
+        epoch.rollover_out = epoch.net_pool(0)?;
+    } else if epoch.is_refundable() {
+        // Stakes are refunded, so only the carried-in pool moves forward
+        epoch.rollover_out = epoch.rollover_in;
+    }
+    market.pending_rollover = market.pending_rollover
+        .checked_add(epoch.rollover_out)
+        .ok_or(BettingError::Overflow)?;
+
+    // One-sided pools and ties are refunded or rolled over, so no fee is taken
+    let fee_bps = market.effective_fee_bps(config);
+    let (fee_amount, net_pool) = if epoch.is_one_sided() || epoch.winning_side == WinningSide::None {
+        (0, epoch.net_pool(0)?)
     } else {
-        WinningSide::Down
+        (epoch.fee_amount(fee_bps)?, epoch.net_pool(fee_bps)?)
     };
 
-    // Calculate fee and net pool
-    let fee_amount = epoch.fee_amount(config.fee_bps)?;
-    let net_pool = epoch.net_pool(config.fee_bps)?;
+    // Record the fee so claims stay consistent if the config or market changes later
+    epoch.fee_bps = if fee_amount > 0 { fee_bps } else { 0 };
 
-    // Transfer fee to treasury if there's a fee
-    if fee_amount > 0 {
-        let seeds = &[
-            b"vault",
-            ctx.accounts.asset_config.usdc_mint.as_ref(),
-            asset_symbol.as_bytes(),
-            &[ctx.bumps.vault],
-        ];
-        let signer_seeds = &[&seeds[..]];
+    // Split the fee between the settler and the treasury
+    let keeper_fee = config.keeper_fee(fee_amount);
+    let treasury_fee = fee_amount - keeper_fee;
 
+    let seeds = &[
+        b"vault",
+        asset_config.usdc_mint.as_ref(),
+        asset_symbol.as_bytes(),
+        &[fees.vault_bump],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    for (destination, amount) in [
+        (fees.treasury_ata.to_account_info(), treasury_fee),
+        (fees.caller_ata.to_account_info(), keeper_fee),
+    ] {
+        if amount == 0 {
+            continue;
+        }
         let transfer_ctx = CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
+            fees.token_program.to_account_info(),
             Transfer {
-                from: ctx.accounts.vault.to_account_info(),
-                to: ctx.accounts.treasury_ata.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
+                from: fees.vault.to_account_info(),
+                to: destination,
+                authority: fees.vault.to_account_info(),
             },
             signer_seeds,
         );
-        token::transfer(transfer_ctx, fee_amount)?;
+        token::transfer(transfer_ctx, amount)?;
     }
 
-    // Pay settle tip to caller (optional, if program has SOL)
-    if config.settle_tip_lamports > 0 {
-        // Transfer lamports from program's vault account to caller
-        // This requires the vault account to have lamports
-        // Simplified: skip if not enough funds
-        let vault_lamports = ctx.accounts.vault.to_account_info().lamports();
-        if vault_lamports >= config.settle_tip_lamports {
-            **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= config.settle_tip_lamports;
-            **ctx.accounts.caller.to_account_info().try_borrow_mut_lamports()? += config.settle_tip_lamports;
-            msg!("Settle tip paid: {} lamports", config.settle_tip_lamports);
-        }
-    }
-
-    // Mark as settled
-    epoch.status = EpochStatus::Settled;
-
     emit!(EpochSettled {
         asset_symbol,
+        timeframe,
         epoch_id,
+        start_price: epoch.start_price,
+        start_expo: epoch.start_expo,
         settle_price,
         settle_expo,
         winning_side: epoch.winning_side.clone(),
         fee_amount,
+        treasury_fee,
+        keeper_fee,
+        keeper,
         net_pool,
-        timestamp: clock.unix_timestamp,
+        rollover_out: epoch.rollover_out,
+        timestamp: now,
     });
 
     msg!("Epoch {} settled", epoch_id);
     msg!("Winning side: {:?}", epoch.winning_side);
-    msg!("Fee: {}, Net pool: {}", fee_amount, net_pool);
+    msg!(
+        "Fee: {} (treasury {}, keeper {}), Net pool: {}",
+        fee_amount,
+        treasury_fee,
+        keeper_fee,
+        net_pool
+    );
 
-    Ok(())
+    Ok(true)
+}
+
+/// Report an oracle source that was rejected or outvoted
+fn emit_outlier(asset_symbol: &str, timeframe: &str, epoch_id: u64, outlier: Outlier, timestamp: i64) {
+    emit!(OracleOutlier {
+        asset_symbol: asset_symbol.to_string(),
+        timeframe: timeframe.to_string(),
+        epoch_id,
+        source_index: outlier.index,
+        source: outlier.source,
+        deviation_bps: outlier.deviation_bps,
+        reason: outlier.reason,
+        timestamp,
+    });
 }
 
 #[derive(Accounts)]
-#[instruction(asset_symbol: String, epoch_id: u64)]
+#[instruction(asset_symbol: String, timeframe: String, epoch_id: u64)]
 pub struct CloseEpoch<'info> {
     #[account(
         mut,
         seeds = [
             b"epoch",
             asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
             &epoch_id.to_be_bytes()
         ],
         bump = epoch.bump,
     )]
     pub epoch: Account<'info, Epoch>,
 
+    #[account(
+        mut,
+        seeds = [b"market", asset_symbol.as_bytes(), timeframe.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
     #[account(
         seeds = [b"asset", asset_symbol.as_bytes()],
         bump = asset_config.bump,
@@ -178,23 +327,42 @@ pub struct CloseEpoch<'info> {
         mut,
         seeds = [b"vault", asset_config.usdc_mint.as_ref(), asset_symbol.as_bytes()],
         bump,
+        address = asset_config.vault @ BettingError::InvalidVault,
         token::mint = asset_config.usdc_mint,
         token::authority = vault,
     )]
     pub vault: Account<'info, TokenAccount>,
 
-    /// Treasury token account to receive fees
+    /// Treasury's associated token account to receive fees
     #[account(
         mut,
-        token::mint = asset_config.usdc_mint,
+        address = get_associated_token_address(&config.treasury, &asset_config.usdc_mint)
+            @ BettingError::InvalidTreasuryAccount,
     )]
     pub treasury_ata: Account<'info, TokenAccount>,
 
-    /// Pyth price account for this asset (read-only)
-    /// CHECK: Validated against asset_config.pyth_price_account
+    /// Primary oracle account: Pyth price account (legacy) or PriceUpdateV2 account (pull)
+    /// Secondary oracle accounts follow as remaining accounts
+    /// CHECK: Matched against the asset's oracle sources and decoded by oracle
     pub pyth_price_account: AccountInfo<'info>,
 
-    /// Caller who triggers settlement (receives tip)
+    /// Lamport tip jar funded through fund_tips
+    #[account(
+        mut,
+        seeds = [b"tip_jar"],
+        bump,
+    )]
+    pub tip_jar: SystemAccount<'info>,
+
+    /// Caller's token account to receive the keeper share of the fee
+    #[account(
+        mut,
+        token::mint = asset_config.usdc_mint,
+        token::authority = caller,
+    )]
+    pub caller_ata: Account<'info, TokenAccount>,
+
+    /// Caller who triggers settlement (receives tip and keeper fee)
     #[account(mut)]
     pub caller: Signer<'info>,
 
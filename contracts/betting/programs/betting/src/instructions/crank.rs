@@ -0,0 +1,226 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use anchor_spl::associated_token::get_associated_token_address;
+use crate::state::{GlobalConfig, AssetConfig, Market, Epoch, EpochStatus};
+use crate::errors::BettingError;
+use super::{
+    lock_if_open, open_epoch, pay_settle_tips, require_primary_oracle, settle_epoch, settle_tips,
+    start_epoch, FeeAccounts,
+};
+
+/// Advance whatever is due for a market in one transaction
+/// Locks the active epoch past cutoff, settles it past end, then opens or starts (and if due, locks) the grid epoch
+/// Idempotent and permissionless; the caller earns one settle tip per transition made, except for
+/// settlements that invalidate the epoch (see settle_tips)
+pub fn crank<'info>(
+    ctx: Context<'_, '_, '_, 'info, Crank<'info>>,
+    asset_symbol: String,
+    timeframe: String,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let grid_epoch_id = ctx.accounts.market.epoch_id_at(now);
+
+    let pyth_price_account = &ctx.accounts.pyth_price_account;
+    require_primary_oracle(&ctx.accounts.asset_config, pyth_price_account)?;
+
+    let mut candidates = vec![pyth_price_account.to_account_info()];
+    candidates.extend_from_slice(ctx.remaining_accounts);
+
+    let mut transitions: u64 = 0;
+    let mut tips: u64 = 0;
+
+    // The grid epoch takes over as active epoch below, so the one it replaces must be passed;
+    // otherwise it would drop out of the crank with its pool and rollover unsettled
+    require!(
+        ctx.accounts.active_epoch.is_some() || !ctx.accounts.market.has_active_epoch_before(grid_epoch_id),
+        BettingError::ActiveEpochRequired
+    );
+
+    // Finish the previously active epoch first so its rollover reaches the next one
+    if let Some(active_epoch) = ctx.accounts.active_epoch.as_mut() {
+        require!(
+            active_epoch.key() != ctx.accounts.grid_epoch.key(),
+            BettingError::DuplicateEpochAccount
+        );
+
+        if now >= active_epoch.cutoff_ts && lock_if_open(active_epoch, now) {
+            transitions += 1;
+            tips += 1;
+        }
+
+        if now >= active_epoch.end_ts && active_epoch.status == EpochStatus::Locked {
+            let settled = settle_epoch(
+                active_epoch,
+                &mut ctx.accounts.market,
+                &ctx.accounts.asset_config,
+                &ctx.accounts.config,
+                &candidates,
+                FeeAccounts {
+                    vault: &ctx.accounts.vault,
+                    vault_bump: ctx.bumps.vault,
+                    treasury_ata: &ctx.accounts.treasury_ata,
+                    caller_ata: &ctx.accounts.caller_ata,
+                    token_program: &ctx.accounts.token_program,
+                },
+                ctx.accounts.caller.key(),
+                now,
+            )?;
+            transitions += 1;
+            tips += settle_tips(settled);
+        }
+    }
+
+    // An empty symbol means init_if_needed just created the grid epoch
+    let grid_epoch = &mut ctx.accounts.grid_epoch;
+    if grid_epoch.asset_symbol.is_empty() {
+        open_epoch(
+            grid_epoch,
+            &mut ctx.accounts.market,
+            &ctx.accounts.asset_config,
+            &ctx.accounts.config,
+            &candidates,
+            grid_epoch_id,
+            ctx.bumps.grid_epoch,
+        )?;
+        transitions += 1;
+        tips += 1;
+    } else if !grid_epoch.started {
+        start_epoch(
+            grid_epoch,
+            &mut ctx.accounts.market,
+            &ctx.accounts.asset_config,
+            &ctx.accounts.config,
+            &candidates,
+            now,
+        )?;
+        transitions += 1;
+        tips += 1;
+    }
+
+    if now >= grid_epoch.cutoff_ts && lock_if_open(grid_epoch, now) {
+        transitions += 1;
+        tips += 1;
+    }
+
+    if transitions == 0 {
+        msg!("Nothing due for {} {}", asset_symbol, timeframe);
+        return Ok(());
+    }
+
+    pay_settle_tips(
+        &ctx.accounts.tip_jar,
+        ctx.bumps.tip_jar,
+        &ctx.accounts.caller,
+        &ctx.accounts.system_program,
+        &ctx.accounts.config,
+        tips,
+        &asset_symbol,
+        &timeframe,
+        grid_epoch_id,
+        now,
+    )?;
+
+    msg!("Crank made {} transition(s)", transitions);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String, timeframe: String)]
+pub struct Crank<'info> {
+    /// Epoch recorded in market.active_epoch_id
+    /// Required while it is an earlier epoch than the grid epoch; omit when it is the grid epoch
+    /// itself or no epoch has started yet
+    #[account(
+        mut,
+        seeds = [
+            b"epoch",
+            asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
+            &market.active_epoch_id.to_be_bytes()
+        ],
+        bump = active_epoch.bump,
+    )]
+    pub active_epoch: Option<Box<Account<'info, Epoch>>>,
+
+    /// Epoch for the current grid slot (created if missing)
+    #[account(
+        init_if_needed,
+        payer = caller,
+        space = Epoch::LEN,
+        seeds = [
+            b"epoch",
+            asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
+            &market.epoch_id_at(Clock::get()?.unix_timestamp).to_be_bytes()
+        ],
+        bump
+    )]
+    pub grid_epoch: Box<Account<'info, Epoch>>,
+
+    #[account(
+        mut,
+        seeds = [b"market", asset_symbol.as_bytes(), timeframe.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Box<Account<'info, AssetConfig>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, GlobalConfig>>,
+
+    /// Vault token account holding USDC for this asset
+    #[account(
+        mut,
+        seeds = [b"vault", asset_config.usdc_mint.as_ref(), asset_symbol.as_bytes()],
+        bump,
+        address = asset_config.vault @ BettingError::InvalidVault,
+        token::mint = asset_config.usdc_mint,
+        token::authority = vault,
+    )]
+    pub vault: Box<Account<'info, TokenAccount>>,
+
+    /// Treasury's associated token account to receive fees
+    #[account(
+        mut,
+        address = get_associated_token_address(&config.treasury, &asset_config.usdc_mint)
+            @ BettingError::InvalidTreasuryAccount,
+    )]
+    pub treasury_ata: Box<Account<'info, TokenAccount>>,
+
+    /// Caller's token account to receive the keeper share of the fee
+    #[account(
+        mut,
+        token::mint = asset_config.usdc_mint,
+        token::authority = caller,
+    )]
+    pub caller_ata: Box<Account<'info, TokenAccount>>,
+
+    /// Primary oracle account: Pyth price account (legacy) or PriceUpdateV2 account (pull)
+    /// Secondary oracle accounts follow as remaining accounts
+    /// CHECK: Matched against the asset's oracle sources and decoded by oracle
+    pub pyth_price_account: AccountInfo<'info>,
+
+    /// Lamport tip jar funded through fund_tips
+    #[account(
+        mut,
+        seeds = [b"tip_jar"],
+        bump,
+    )]
+    pub tip_jar: SystemAccount<'info>,
+
+    /// Anyone can crank (pays rent for a new epoch, receives tips and keeper fee)
+    #[account(mut)]
+    pub caller: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
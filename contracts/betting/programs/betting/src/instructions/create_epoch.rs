@@ -1,28 +1,188 @@
 use anchor_lang::prelude::*;
-use crate::state::{GlobalConfig, AssetConfig, Epoch, EpochStatus, WinningSide, ts_to_epoch_id, epoch_id_to_start_ts};
-use crate::events::EpochCreated;
-
-/// Create a new betting epoch for an asset
-/// Anyone can call this to create the next epoch on the grid
-pub fn create_epoch(
-    ctx: Context<CreateEpoch>,
-    asset_symbol: String,
+use crate::state::{GlobalConfig, AssetConfig, Market, Epoch, EpochStatus, WinningSide};
+use crate::errors::BettingError;
+use crate::events::{EpochCreated, EpochStarted};
+use crate::oracle::{self, OracleError};
+use super::require_primary_oracle;
+
+/// Create a new betting epoch for a market
+/// Anyone can call this to create (or start a pre-created) epoch for the market's current grid slot
+/// The oracle price at the slot's start is recorded as the start price the epoch settles against
+pub fn create_epoch<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreateEpoch<'info>>,
+    _asset_symbol: String,
+    _timeframe: String,
 ) -> Result<()> {
     let config = &ctx.accounts.config;
-    let asset_config = &mut ctx.accounts.asset_config;
+    let now = Clock::get()?.unix_timestamp;
+
+    // Calculate epoch ID based on current time and the market's epoch grid
+    let epoch_id = ctx.accounts.market.epoch_id_at(now);
+
+    let pyth_price_account = &ctx.accounts.pyth_price_account;
+    require_primary_oracle(&ctx.accounts.asset_config, pyth_price_account)?;
+
+    let mut candidates = vec![pyth_price_account.to_account_info()];
+    candidates.extend_from_slice(ctx.remaining_accounts);
+
+    // An empty symbol means init_if_needed just created the account
     let epoch = &mut ctx.accounts.epoch;
+    if epoch.asset_symbol.is_empty() {
+        return open_epoch(
+            epoch,
+            &mut ctx.accounts.market,
+            &ctx.accounts.asset_config,
+            config,
+            &candidates,
+            epoch_id,
+            ctx.bumps.epoch,
+        );
+    }
 
-    let clock = Clock::get()?;
-    let now = clock.unix_timestamp;
+    require!(!epoch.started, BettingError::EpochAlreadyStarted);
+    start_epoch(
+        epoch,
+        &mut ctx.accounts.market,
+        &ctx.accounts.asset_config,
+        config,
+        &candidates,
+        now,
+    )
+}
 
-    // Calculate epoch ID based on current time and epoch grid
-    let epoch_id = ts_to_epoch_id(now, config.epoch_length_secs);
-    let start_ts = epoch_id_to_start_ts(epoch_id, config.epoch_length_secs);
-    let end_ts = start_ts + config.epoch_length_secs as i64;
-    let cutoff_ts = end_ts - config.cutoff_secs as i64;
+/// Pre-create an epoch for a future grid slot so bets can be queued ahead of time
+/// The start price is captured when the epoch starts (create_epoch or crank)
+pub fn create_epoch_at(
+    ctx: Context<CreateEpochAt>,
+    _asset_symbol: String,
+    _timeframe: String,
+    epoch_id: u64,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let market = &mut ctx.accounts.market;
+    let current_epoch_id = market.epoch_id_at(Clock::get()?.unix_timestamp);
+
+    require!(
+        epoch_id > current_epoch_id
+            && epoch_id - current_epoch_id <= config.max_future_epochs as u64,
+        BettingError::EpochOutOfRange
+    );
+
+    init_epoch(
+        &mut ctx.accounts.epoch,
+        market,
+        &ctx.accounts.asset_config,
+        epoch_id,
+        ctx.bumps.epoch,
+    );
+
+    Ok(())
+}
+
+/// Initialize a freshly created epoch account for the current grid slot `epoch_id` and start it
+/// Refuses to open without a start price from every oracle source; like start_epoch, the price
+/// must describe the slot's start_ts, so an epoch opened late does not get a later start price
+pub(crate) fn open_epoch<'info>(
+    epoch: &mut Epoch,
+    market: &mut Market,
+    asset_config: &AssetConfig,
+    config: &GlobalConfig,
+    candidates: &[AccountInfo<'info>],
+    epoch_id: u64,
+    bump: u8,
+) -> Result<()> {
+    let start_ts = market.epoch_start_ts(epoch_id);
+    let start = oracle::quorum::read_quorum_price(candidates, &config.pyth_program, asset_config, start_ts)
+        .map_err(|failure| {
+            msg!("Invalid oracle price: {:?}", failure.reason);
+            error!(BettingError::InvalidPrice)
+        })?
+        .price;
+
+    epoch.start_price = start.price;
+    epoch.start_expo = start.expo;
+    epoch.start_publish_ts = start.publish_time;
+    init_epoch(epoch, market, asset_config, epoch_id, bump);
+
+    // Update active epoch ID and hand over any rolled-over pool
+    epoch.started = true;
+    epoch.rollover_in = market.pending_rollover;
+    market.active_epoch_id = epoch_id;
+    market.pending_rollover = 0;
+
+    Ok(())
+}
+
+/// Make a pre-created epoch the market's active epoch once its slot begins
+/// Captures the price for start_ts if one is usable; otherwise settlement retries at start_ts
+pub(crate) fn start_epoch<'info>(
+    epoch: &mut Epoch,
+    market: &mut Market,
+    asset_config: &AssetConfig,
+    config: &GlobalConfig,
+    candidates: &[AccountInfo<'info>],
+    now: i64,
+) -> Result<()> {
+    // The epoch was laid out ahead of time, so check it still sits on the slot running now
+    require!(
+        epoch.start_ts == market.epoch_start_ts(epoch.epoch_id) && epoch.start_ts <= now,
+        BettingError::EpochGridMismatch
+    );
+
+    match oracle::quorum::read_quorum_price(candidates, &config.pyth_program, asset_config, epoch.start_ts) {
+        Ok(start) => {
+            epoch.start_price = start.price.price;
+            epoch.start_expo = start.price.expo;
+            epoch.start_publish_ts = start.price.publish_time;
+        }
+        // Omitting or doubling an oracle account must not be a way to skip the start price
+        Err(failure) if failure.reason == OracleError::MissingAccount => {
+            return err!(BettingError::OracleAccountMissing);
+        }
+        Err(failure) if failure.reason == OracleError::DuplicateAccount => {
+            return err!(BettingError::DuplicateOracleAccount);
+        }
+        Err(failure) => {
+            msg!("No start price yet: {:?}", failure.reason);
+        }
+    }
+
+    epoch.started = true;
+    epoch.rollover_in = market.pending_rollover;
+    market.active_epoch_id = epoch.epoch_id;
+    market.pending_rollover = 0;
+
+    emit!(EpochStarted {
+        asset_symbol: epoch.asset_symbol.clone(),
+        timeframe: epoch.timeframe.clone(),
+        epoch_id: epoch.epoch_id,
+        start_price: epoch.start_price,
+        start_expo: epoch.start_expo,
+        rollover_in: epoch.rollover_in,
+        timestamp: now,
+    });
+
+    msg!("Epoch started: {}", epoch.epoch_id);
+    msg!("Start price: {} (expo {})", epoch.start_price, epoch.start_expo);
+
+    Ok(())
+}
+
+/// Lay out an epoch on the market's grid; start price, rollover and activation are left to the caller
+fn init_epoch(
+    epoch: &mut Epoch,
+    market: &mut Market,
+    asset_config: &AssetConfig,
+    epoch_id: u64,
+    bump: u8,
+) {
+    let start_ts = market.epoch_start_ts(epoch_id);
+    let end_ts = start_ts + market.epoch_length_for(epoch_id) as i64;
+    let cutoff_ts = end_ts - market.cutoff_secs as i64;
 
     // Initialize epoch
-    epoch.asset_symbol = asset_symbol.clone();
+    epoch.asset_symbol = market.asset_symbol.clone();
+    epoch.timeframe = market.timeframe.clone();
     epoch.epoch_id = epoch_id;
     epoch.start_ts = start_ts;
     epoch.cutoff_ts = cutoff_ts;
@@ -33,29 +193,82 @@ pub fn create_epoch(
     epoch.winning_side = WinningSide::None;
     epoch.sum_up = 0;
     epoch.sum_down = 0;
+    epoch.rollover_in = 0;
+    epoch.rollover_out = 0;
+    epoch.rolled_over = false;
+    epoch.fee_bps = 0;
+    epoch.started = false;
     epoch.mint = asset_config.usdc_mint;
-    epoch.bump = ctx.bumps.epoch;
-
-    // Update active epoch ID
-    asset_config.active_epoch_id = epoch_id;
+    epoch.bump = bump;
+    market.last_epoch_id = market.last_epoch_id.max(epoch_id);
 
     emit!(EpochCreated {
-        asset_symbol,
+        asset_symbol: market.asset_symbol.clone(),
+        timeframe: market.timeframe.clone(),
         epoch_id,
         start_ts,
         cutoff_ts,
         end_ts,
+        start_price: epoch.start_price,
+        start_expo: epoch.start_expo,
     });
 
     msg!("Epoch created: {}", epoch_id);
     msg!("Start: {}, Cutoff: {}, End: {}", start_ts, cutoff_ts, end_ts);
-
-    Ok(())
+    msg!("Start price: {} (expo {})", epoch.start_price, epoch.start_expo);
 }
 
 #[derive(Accounts)]
-#[instruction(asset_symbol: String)]
+#[instruction(asset_symbol: String, timeframe: String)]
 pub struct CreateEpoch<'info> {
+    /// Epoch for the current grid slot (created, or started if it was pre-created)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = Epoch::LEN,
+        seeds = [
+            b"epoch",
+            asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
+            &market.epoch_id_at(Clock::get()?.unix_timestamp).to_be_bytes()
+        ],
+        bump
+    )]
+    pub epoch: Account<'info, Epoch>,
+
+    #[account(
+        mut,
+        seeds = [b"market", asset_symbol.as_bytes(), timeframe.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Box<Account<'info, AssetConfig>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Primary oracle account: Pyth price account (legacy) or PriceUpdateV2 account (pull)
+    /// Secondary oracle accounts follow as remaining accounts
+    /// CHECK: Matched against the asset's oracle sources and decoded by oracle
+    pub pyth_price_account: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String, timeframe: String, epoch_id: u64)]
+pub struct CreateEpochAt<'info> {
     #[account(
         init,
         payer = payer,
@@ -63,7 +276,8 @@ pub struct CreateEpoch<'info> {
         seeds = [
             b"epoch",
             asset_symbol.as_bytes(),
-            &ts_to_epoch_id(Clock::get()?.unix_timestamp, config.epoch_length_secs).to_be_bytes()
+            timeframe.as_bytes(),
+            &epoch_id.to_be_bytes()
         ],
         bump
     )]
@@ -71,6 +285,12 @@ pub struct CreateEpoch<'info> {
 
     #[account(
         mut,
+        seeds = [b"market", asset_symbol.as_bytes(), timeframe.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Box<Account<'info, Market>>,
+
+    #[account(
         seeds = [b"asset", asset_symbol.as_bytes()],
         bump = asset_config.bump,
     )]
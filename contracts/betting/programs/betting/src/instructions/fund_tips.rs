@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::GlobalConfig;
+use crate::events::TipSkipped;
+
+/// Deposit lamports into the tip jar that pays settlers
+/// Anyone can fund it
+pub fn fund_tips(ctx: Context<FundTips>, amount: u64) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.funder.to_account_info(),
+                to: ctx.accounts.tip_jar.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    msg!("Tip jar funded: {} lamports", amount);
+    msg!("Tip jar balance: {}", ctx.accounts.tip_jar.lamports());
+
+    Ok(())
+}
+
+/// Pay `tips` settle tips to the caller from the tip jar, keeping the jar rent-exempt
+/// Settlement goes ahead when the jar runs dry; a TipSkipped event is emitted instead
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pay_settle_tips<'info>(
+    tip_jar: &SystemAccount<'info>,
+    tip_jar_bump: u8,
+    caller: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    config: &GlobalConfig,
+    tips: u64,
+    asset_symbol: &str,
+    timeframe: &str,
+    epoch_id: u64,
+    now: i64,
+) -> Result<()> {
+    let amount = config.settle_tip_lamports.saturating_mul(tips);
+    if amount == 0 {
+        return Ok(());
+    }
+
+    let available = tip_jar
+        .lamports()
+        .saturating_sub(Rent::get()?.minimum_balance(0));
+    if available < amount {
+        emit!(TipSkipped {
+            asset_symbol: asset_symbol.to_string(),
+            timeframe: timeframe.to_string(),
+            epoch_id,
+            caller: caller.key(),
+            tip_lamports: amount,
+            available_lamports: available,
+            timestamp: now,
+        });
+        msg!("Tip jar empty, settle tip skipped");
+        return Ok(());
+    }
+
+    let signer_seeds: &[&[&[u8]]] = &[&[b"tip_jar", &[tip_jar_bump]]];
+    system_program::transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: tip_jar.to_account_info(),
+                to: caller.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )?;
+    msg!("Settle tip paid: {} lamports", amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FundTips<'info> {
+    /// System-owned PDA holding lamports for settle tips
+    #[account(
+        mut,
+        seeds = [b"tip_jar"],
+        bump,
+    )]
+    pub tip_jar: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,202 @@
+use anchor_lang::prelude::*;
+use crate::state::{GlobalConfig, AssetConfig, Market, PendingChange, GovernanceChange, AssetOracleChange};
+use crate::errors::BettingError;
+use crate::events::{ChangeQueued, ChangeExecuted, ChangeCancelled};
+use super::{apply_config_change, apply_asset_oracle_change, apply_market_change};
+
+/// Queue a sensitive change; it can be executed once GlobalConfig.timelock_secs has elapsed
+pub fn queue_change(ctx: Context<QueueChange>, change: GovernanceChange) -> Result<()> {
+    if let GovernanceChange::AssetOracle(AssetOracleChange { asset_symbol, secondary_oracles, .. }) = &change {
+        require!(
+            asset_symbol.len() <= AssetConfig::MAX_SYMBOL_LEN,
+            BettingError::AssetSymbolTooLong
+        );
+        require!(
+            secondary_oracles.as_ref().map_or(0, Vec::len) <= AssetConfig::MAX_SECONDARY_ORACLES,
+            BettingError::TooManyOracles
+        );
+    }
+    if let GovernanceChange::Market(change) = &change {
+        require!(
+            change.asset_symbol.len() <= AssetConfig::MAX_SYMBOL_LEN,
+            BettingError::AssetSymbolTooLong
+        );
+        require!(
+            change.timeframe.len() <= Market::MAX_TIMEFRAME_LEN,
+            BettingError::TimeframeTooLong
+        );
+    }
+
+    let config = &mut ctx.accounts.config;
+    let pending_change = &mut ctx.accounts.pending_change;
+    let now = Clock::get()?.unix_timestamp;
+
+    pending_change.change_id = config.next_change_id;
+    pending_change.change = change.clone();
+    pending_change.proposer = ctx.accounts.admin.key();
+    pending_change.queued_at = now;
+    pending_change.eta = now + config.timelock_secs as i64;
+    pending_change.bump = ctx.bumps.pending_change;
+
+    config.next_change_id += 1;
+
+    emit!(ChangeQueued {
+        change_id: pending_change.change_id,
+        change,
+        proposer: pending_change.proposer,
+        eta: pending_change.eta,
+        timestamp: now,
+    });
+
+    msg!("Change {} queued, executable at {}", pending_change.change_id, pending_change.eta);
+
+    Ok(())
+}
+
+/// Apply a queued change once its timelock has elapsed
+/// Anyone can execute; asset and market changes require the matching asset_config or market account
+pub fn execute_change(ctx: Context<ExecuteChange>, change_id: u64) -> Result<()> {
+    let pending_change = &ctx.accounts.pending_change;
+    let now = Clock::get()?.unix_timestamp;
+
+    require!(now >= pending_change.eta, BettingError::TimelockNotElapsed);
+
+    match &pending_change.change {
+        GovernanceChange::Config(change) => {
+            apply_config_change(&mut ctx.accounts.config, change, now)?;
+        }
+        GovernanceChange::AssetOracle(change) => {
+            let asset_config = ctx
+                .accounts
+                .asset_config
+                .as_mut()
+                .ok_or(BettingError::ChangeAssetMismatch)?;
+            require!(
+                asset_config.asset_symbol == change.asset_symbol,
+                BettingError::ChangeAssetMismatch
+            );
+            apply_asset_oracle_change(asset_config, change)?;
+        }
+        GovernanceChange::Market(change) => {
+            let market = ctx
+                .accounts
+                .market
+                .as_mut()
+                .ok_or(BettingError::ChangeMarketMismatch)?;
+            require!(
+                market.asset_symbol == change.asset_symbol && market.timeframe == change.timeframe,
+                BettingError::ChangeMarketMismatch
+            );
+            apply_market_change(market, change, &ctx.accounts.config, now)?;
+        }
+    }
+
+    emit!(ChangeExecuted {
+        change_id,
+        change: pending_change.change.clone(),
+        timestamp: now,
+    });
+
+    msg!("Change {} executed", change_id);
+
+    Ok(())
+}
+
+/// Cancel a queued change before it is executed
+/// Intentionally allowed after eta as well: the timelock protects users from changes, and
+/// cancelling one only keeps the current settings, so there is nothing to wait for
+pub fn cancel_change(_ctx: Context<CancelChange>, change_id: u64) -> Result<()> {
+    emit!(ChangeCancelled {
+        change_id,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Change {} cancelled", change_id);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct QueueChange<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = PendingChange::LEN,
+        seeds = [b"pending_change".as_ref(), &config.next_change_id.to_be_bytes()],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(change_id: u64)]
+pub struct ExecuteChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_change".as_ref(), &change_id.to_be_bytes()],
+        bump = pending_change.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Required for asset oracle changes
+    #[account(mut)]
+    pub asset_config: Option<Account<'info, AssetConfig>>,
+
+    /// Required for market changes
+    #[account(mut)]
+    pub market: Option<Account<'info, Market>>,
+
+    /// CHECK: Receives the PendingChange rent; matched by has_one
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(change_id: u64)]
+pub struct CancelChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"pending_change".as_ref(), &change_id.to_be_bytes()],
+        bump = pending_change.bump,
+        has_one = proposer,
+        close = proposer
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// CHECK: Receives the PendingChange rent; matched by has_one
+    #[account(mut)]
+    pub proposer: AccountInfo<'info>,
+
+    pub admin: Signer<'info>,
+}
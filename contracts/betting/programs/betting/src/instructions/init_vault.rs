@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use crate::state::{GlobalConfig, AssetConfig};
+use crate::errors::BettingError;
+
+/// Create the USDC vault for an asset and record it in the asset config
+/// The vault is a PDA token account that is its own authority
+pub fn init_vault(ctx: Context<InitVault>, asset_symbol: String) -> Result<()> {
+    let asset_config = &mut ctx.accounts.asset_config;
+    asset_config.vault = ctx.accounts.vault.key();
+
+    msg!("Vault initialized for {}: {}", asset_symbol, asset_config.vault);
+    msg!("Mint: {}", asset_config.usdc_mint);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String)]
+pub struct InitVault<'info> {
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", usdc_mint.key().as_ref(), asset_symbol.as_bytes()],
+        bump,
+        token::mint = usdc_mint,
+        token::authority = vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    #[account(
+        address = asset_config.usdc_mint @ BettingError::WrongMint,
+        constraint = usdc_mint.decimals == AssetConfig::USDC_DECIMALS @ BettingError::InvalidMintDecimals
+    )]
+    pub usdc_mint: Account<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.admin
+            || authority.key() == config.asset_manager @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Admin or asset manager
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
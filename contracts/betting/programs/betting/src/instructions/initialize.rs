@@ -1,30 +1,53 @@
 use anchor_lang::prelude::*;
 use crate::state::GlobalConfig;
+use crate::errors::BettingError;
+use crate::program::Betting;
 
 /// Initialize the global configuration for the betting protocol
+/// Only the program's upgrade authority can call this
+#[allow(clippy::too_many_arguments)]
 pub fn initialize(
     ctx: Context<Initialize>,
     admin: Pubkey,
     treasury: Pubkey,
+    pyth_program: Pubkey,
     fee_bps: u16,
     settle_tip_lamports: u64,
     cutoff_secs: u32,
     epoch_length_secs: u32,
 ) -> Result<()> {
+    require!(fee_bps <= GlobalConfig::MAX_FEE_BPS, BettingError::FeeTooHigh);
+    require!(epoch_length_secs > 0, BettingError::InvalidEpochLength);
+    require!(cutoff_secs < epoch_length_secs, BettingError::InvalidCutoff);
+
     let config = &mut ctx.accounts.config;
 
     config.admin = admin;
     config.treasury = treasury;
+    config.pyth_program = pyth_program;
     config.fee_bps = fee_bps;
     config.settle_tip_lamports = settle_tip_lamports;
     config.cutoff_secs = cutoff_secs;
     config.epoch_length_secs = epoch_length_secs;
     config.paused = false;
     config.bump = ctx.bumps.config;
+    // Every role starts with the admin until delegated
+    config.pending_admin = Pubkey::default();
+    config.pauser = admin;
+    config.fee_manager = admin;
+    config.asset_manager = admin;
+    // No timelock until the admin opts in through update_config
+    config.timelock_secs = 0;
+    config.next_change_id = 0;
+    config.keeper_fee_bps = 0;
+    config.max_future_epochs = 0;
+    config.cancel_fee_bps = 0;
+    config.legacy_fee_bps = 0;
 
     msg!("Betting protocol initialized");
     msg!("Admin: {}", admin);
     msg!("Treasury: {}", treasury);
+    msg!("Pyth program: {}", pyth_program);
     msg!("Fee: {} bps", fee_bps);
 
     Ok(())
@@ -44,5 +67,15 @@ pub struct Initialize<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, Betting>,
+
+    /// Upgrade authority recorded here must be the payer
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(payer.key())
+            @ BettingError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+
     pub system_program: Program<'info, System>,
 }
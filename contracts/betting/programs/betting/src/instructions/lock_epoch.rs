@@ -7,8 +7,9 @@ use crate::events::EpochLocked;
 /// This is idempotent and anyone can call it
 pub fn lock_epoch(
     ctx: Context<LockEpoch>,
-    asset_symbol: String,
-    epoch_id: u64,
+    _asset_symbol: String,
+    _timeframe: String,
+    _epoch_id: u64,
 ) -> Result<()> {
     let epoch = &mut ctx.accounts.epoch;
 
@@ -19,36 +20,44 @@ pub fn lock_epoch(
         BettingError::NotYetCutoff
     );
 
-    // Idempotent: if already locked or settled, do nothing
-    if epoch.status == EpochStatus::Locked || epoch.status == EpochStatus::Settled {
+    lock_if_open(epoch, clock.unix_timestamp);
+
+    Ok(())
+}
+
+/// Move an open epoch to Locked, returning whether anything changed
+/// Locked, settled and invalid epochs are left alone
+pub(crate) fn lock_if_open(epoch: &mut Epoch, now: i64) -> bool {
+    if epoch.status != EpochStatus::Open {
         msg!("Epoch already locked or settled");
-        return Ok(());
+        return false;
     }
 
-    // Lock the epoch
     epoch.status = EpochStatus::Locked;
 
     emit!(EpochLocked {
-        asset_symbol,
-        epoch_id,
-        timestamp: clock.unix_timestamp,
+        asset_symbol: epoch.asset_symbol.clone(),
+        timeframe: epoch.timeframe.clone(),
+        epoch_id: epoch.epoch_id,
+        timestamp: now,
         sum_up: epoch.sum_up,
         sum_down: epoch.sum_down,
     });
 
-    msg!("Epoch {} locked", epoch_id);
+    msg!("Epoch {} locked", epoch.epoch_id);
 
-    Ok(())
+    true
 }
 
 #[derive(Accounts)]
-#[instruction(asset_symbol: String, epoch_id: u64)]
+#[instruction(asset_symbol: String, timeframe: String, epoch_id: u64)]
 pub struct LockEpoch<'info> {
     #[account(
         mut,
         seeds = [
             b"epoch",
             asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
             &epoch_id.to_be_bytes()
         ],
         bump = epoch.bump,
@@ -0,0 +1,216 @@
+use anchor_lang::prelude::*;
+use crate::state::{GlobalConfig, AssetConfig, Market, MarketChange, epoch_id_to_start_ts};
+use crate::errors::BettingError;
+use crate::events::MarketUpdated;
+
+/// Open a new timeframe market for a configured asset
+/// Length and cutoff default to the global settings; without a fee override the market follows the global fee
+/// The grid starts at the asset's grid offset so its epochs roll over apart from other assets
+pub fn create_market(
+    ctx: Context<CreateMarket>,
+    asset_symbol: String,
+    timeframe: String,
+    epoch_length_secs: Option<u32>,
+    cutoff_secs: Option<u32>,
+    fee_bps: Option<u16>,
+) -> Result<()> {
+    require!(!timeframe.is_empty(), BettingError::EmptyTimeframe);
+    require!(
+        timeframe.len() <= Market::MAX_TIMEFRAME_LEN,
+        BettingError::TimeframeTooLong
+    );
+
+    let config = &ctx.accounts.config;
+    let market = &mut ctx.accounts.market;
+    let epoch_length_secs = epoch_length_secs.unwrap_or(config.epoch_length_secs);
+    let cutoff_secs = cutoff_secs.unwrap_or(config.cutoff_secs);
+
+    // Checked before any grid arithmetic divides by the length
+    require!(epoch_length_secs > 0, BettingError::InvalidEpochLength);
+    require!(cutoff_secs < epoch_length_secs, BettingError::InvalidCutoff);
+
+    market.asset_symbol = asset_symbol;
+    market.timeframe = timeframe;
+    market.epoch_length_secs = epoch_length_secs;
+    market.cutoff_secs = cutoff_secs;
+    market.fee_bps = None;
+    let asset_config = &mut ctx.accounts.asset_config;
+    market.grid_origin_ts =
+        epoch_id_to_start_ts(0, epoch_length_secs, asset_config.grid_offset_secs);
+    asset_config.market_count = asset_config.market_count
+        .checked_add(1)
+        .ok_or(BettingError::Overflow)?;
+    market.grid_origin_epoch_id = 0;
+    market.prev_epoch_length_secs = epoch_length_secs;
+    market.active_epoch_id = 0;
+    market.last_epoch_id = 0;
+    market.pending_rollover = 0;
+    market.bump = ctx.bumps.market;
+
+    let change = MarketChange {
+        fee_bps,
+        ..Default::default()
+    };
+    apply_market_change(market, &change, config, Clock::get()?.unix_timestamp)
+}
+
+/// Update a market's timing or fee override (only provided fields change)
+/// The asset manager may change timing, the fee manager the fee override; the admin may change both
+/// Raising the effective fee must go through queue_change while a timelock is set
+pub fn update_market(
+    ctx: Context<UpdateMarket>,
+    asset_symbol: String,
+    timeframe: String,
+    epoch_length_secs: Option<u32>,
+    cutoff_secs: Option<u32>,
+    fee_bps: Option<u16>,
+    clear_fee_bps: bool,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let authority = ctx.accounts.authority.key();
+    let change = MarketChange {
+        asset_symbol,
+        timeframe,
+        epoch_length_secs,
+        cutoff_secs,
+        fee_bps,
+        clear_fee_bps,
+    };
+
+    if authority != config.admin {
+        if authority != config.asset_manager {
+            require!(
+                epoch_length_secs.is_none() && cutoff_secs.is_none(),
+                BettingError::Unauthorized
+            );
+        }
+        if authority != config.fee_manager {
+            require!(fee_bps.is_none() && !clear_fee_bps, BettingError::Unauthorized);
+        }
+    }
+
+    let market = &mut ctx.accounts.market;
+    require!(
+        config.timelock_secs == 0 || !change.is_sensitive(market, config),
+        BettingError::TimelockRequired
+    );
+
+    apply_market_change(market, &change, config, Clock::get()?.unix_timestamp)
+}
+
+/// Validate and apply a market change, emitting MarketUpdated
+/// A new epoch length takes effect from the next grid boundary so the current epoch keeps its ID and slot
+pub(crate) fn apply_market_change(
+    market: &mut Market,
+    change: &MarketChange,
+    config: &GlobalConfig,
+    now: i64,
+) -> Result<()> {
+    if let Some(epoch_length_secs) = change.epoch_length_secs {
+        require!(epoch_length_secs > 0, BettingError::InvalidEpochLength);
+        // Pre-created epochs already hold timestamps from the current grid
+        require!(
+            epoch_length_secs == market.epoch_length_secs
+                || market.last_epoch_id <= market.epoch_id_at(now),
+            BettingError::EpochsPreCreated
+        );
+        market.set_epoch_length(epoch_length_secs, now);
+        msg!(
+            "Epoch length: {}s from epoch {} (ts {})",
+            epoch_length_secs,
+            market.grid_origin_epoch_id,
+            market.grid_origin_ts
+        );
+    }
+
+    if let Some(cutoff_secs) = change.cutoff_secs {
+        market.cutoff_secs = cutoff_secs;
+        msg!("Cutoff: {}s", cutoff_secs);
+    }
+
+    // The cutoff must fit both the slot running now and the slots that follow it
+    let current_length = market.epoch_length_for(market.epoch_id_at(now));
+    require!(
+        market.cutoff_secs < market.epoch_length_secs && market.cutoff_secs < current_length,
+        BettingError::InvalidCutoff
+    );
+
+    let fee_bps = change.new_fee_bps(market);
+    if let Some(fee_bps) = fee_bps {
+        require!(fee_bps <= GlobalConfig::MAX_FEE_BPS, BettingError::FeeTooHigh);
+    }
+    market.fee_bps = fee_bps;
+    msg!("Fee: {} bps", market.effective_fee_bps(config));
+
+    emit!(MarketUpdated {
+        asset_symbol: market.asset_symbol.clone(),
+        timeframe: market.timeframe.clone(),
+        epoch_length_secs: market.epoch_length_secs,
+        cutoff_secs: market.cutoff_secs,
+        fee_bps: market.fee_bps,
+        grid_origin_ts: market.grid_origin_ts,
+        timestamp: now,
+    });
+
+    msg!("Market {} {} updated", market.asset_symbol, market.timeframe);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String, timeframe: String)]
+pub struct CreateMarket<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = Market::LEN,
+        seeds = [b"market", asset_symbol.as_bytes(), timeframe.as_bytes()],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+
+    /// Asset must be configured through set_asset_feed first
+    #[account(
+        mut,
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.admin
+            || authority.key() == config.asset_manager @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Admin or asset manager
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String, timeframe: String)]
+pub struct UpdateMarket<'info> {
+    #[account(
+        mut,
+        seeds = [b"market", asset_symbol.as_bytes(), timeframe.as_bytes()],
+        bump = market.bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.admin
+            || authority.key() == config.asset_manager
+            || authority.key() == config.fee_manager @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Admin, asset manager or fee manager
+    pub authority: Signer<'info>,
+}
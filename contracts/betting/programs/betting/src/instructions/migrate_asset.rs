@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::{AssetConfig, LegacyAssetConfig};
+use crate::errors::BettingError;
+
+/// Upgrade an asset config written before oracle sources and markets to the current layout
+/// The asset keeps its legacy Pyth feed and mint with default oracle guards; pass the old vault
+/// PDA if it exists so it stays the asset's vault. Markets are created afterwards with create_market
+/// Idempotent and permissionless: already-migrated assets are left alone; the payer covers the extra rent
+pub fn migrate_asset(ctx: Context<MigrateAsset>, asset_symbol: String) -> Result<()> {
+    let asset_config = &ctx.accounts.asset_config;
+
+    if asset_config.data_len() == AssetConfig::LEN {
+        msg!("Asset already migrated");
+        return Ok(());
+    }
+
+    let legacy = LegacyAssetConfig::from_account_data(&asset_config.try_borrow_data()?)?;
+
+    let vault = match &ctx.accounts.vault {
+        Some(vault) => {
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"vault", legacy.usdc_mint.as_ref(), asset_symbol.as_bytes()],
+                &crate::ID,
+            );
+            require_keys_eq!(vault.key(), expected, BettingError::InvalidVault);
+            require_keys_eq!(vault.mint, legacy.usdc_mint, BettingError::WrongMint);
+            vault.key()
+        }
+        None => Pubkey::default(),
+    };
+    let migrated = legacy.into_asset_config(vault);
+
+    super::migrate_epoch::grow_account(
+        asset_config,
+        AssetConfig::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+    migrated.try_serialize(&mut &mut asset_config.try_borrow_mut_data()?[..])?;
+
+    msg!("Asset migrated: {}", asset_symbol);
+    msg!("Vault: {}", migrated.vault);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String)]
+pub struct MigrateAsset<'info> {
+    /// CHECK: Asset config in either layout; owner and PDA checked here, discriminator and size in the handler
+    #[account(
+        mut,
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub asset_config: AccountInfo<'info>,
+
+    /// Vault PDA ["vault", usdc_mint, asset_symbol] left by the old program, if it was created
+    pub vault: Option<Account<'info, TokenAccount>>,
+
+    /// Anyone can migrate an asset (pays the extra rent)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
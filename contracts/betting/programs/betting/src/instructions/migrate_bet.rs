@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use crate::state::{UserBet, LegacyUserBet};
+
+/// Upgrade a bet written before timeframe markets to the hedged stake_up/stake_down layout
+/// The old stake moves to the side it was placed on and the timeframe is left empty; an empty
+/// timeframe seed derives the bet's existing address, so claim and refund take timeframe ""
+/// Idempotent and permissionless: already-migrated bets are left alone; the payer covers the extra rent
+pub fn migrate_bet(
+    ctx: Context<MigrateBet>,
+    _asset_symbol: String,
+    _epoch_id: u64,
+) -> Result<()> {
+    let user_bet = &ctx.accounts.user_bet;
+
+    if user_bet.data_len() == UserBet::LEN {
+        msg!("Bet already migrated");
+        return Ok(());
+    }
+
+    let legacy = LegacyUserBet::from_account_data(&user_bet.try_borrow_data()?)?;
+    let migrated = UserBet::from(legacy);
+
+    super::migrate_epoch::grow_account(
+        user_bet,
+        UserBet::LEN,
+        &ctx.accounts.payer,
+        &ctx.accounts.system_program,
+    )?;
+    migrated.try_serialize(&mut &mut user_bet.try_borrow_mut_data()?[..])?;
+
+    msg!(
+        "Bet migrated: up {} USDC, down {} USDC",
+        migrated.stake_up,
+        migrated.stake_down
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String, epoch_id: u64)]
+pub struct MigrateBet<'info> {
+    /// CHECK: Bet in either layout; owner and PDA checked here, discriminator and size in the handler
+    #[account(
+        mut,
+        seeds = [
+            b"bet",
+            user.key().as_ref(),
+            asset_symbol.as_bytes(),
+            &epoch_id.to_be_bytes()
+        ],
+        bump,
+        owner = crate::ID,
+    )]
+    pub user_bet: AccountInfo<'info>,
+
+    /// CHECK: Bet owner, only used to derive the bet PDA
+    pub user: AccountInfo<'info>,
+
+    /// Anyone can migrate a bet (pays the extra rent)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
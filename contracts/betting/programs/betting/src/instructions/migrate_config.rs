@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::state::{GlobalConfig, LegacyGlobalConfig};
+use crate::errors::BettingError;
+
+/// Upgrade the global config written before roles and timelocks to the current layout
+/// Roles start with the admin as after initialize, and the current fee is recorded as
+/// legacy_fee_bps for migrate_epoch. Run once before migrating assets, epochs or bets
+/// Only the recorded admin can migrate, since it also sets the Pyth program; the admin pays the extra rent
+pub fn migrate_config(ctx: Context<MigrateConfig>, pyth_program: Pubkey) -> Result<()> {
+    let config = &ctx.accounts.config;
+
+    if config.data_len() == GlobalConfig::LEN {
+        msg!("Config already migrated");
+        return Ok(());
+    }
+
+    let legacy = LegacyGlobalConfig::from_account_data(&config.try_borrow_data()?)?;
+    require_keys_eq!(legacy.admin, ctx.accounts.admin.key(), BettingError::Unauthorized);
+    let migrated = legacy.into_config(pyth_program);
+
+    super::migrate_epoch::grow_account(
+        config,
+        GlobalConfig::LEN,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+    )?;
+    migrated.try_serialize(&mut &mut config.try_borrow_mut_data()?[..])?;
+
+    msg!("Config migrated");
+    msg!("Pyth program: {}", pyth_program);
+    msg!("Legacy fee: {} bps", migrated.legacy_fee_bps);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: Config in either layout; owner and PDA checked here, discriminator, size and admin in the handler
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        owner = crate::ID,
+    )]
+    pub config: AccountInfo<'info>,
+
+    /// Admin recorded in the old config (pays the extra rent)
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use crate::state::{GlobalConfig, Epoch, LegacyEpoch};
+
+/// Upgrade an epoch written before timeframe markets to the current layout
+/// The epoch keeps its address under an empty timeframe. Settled epochs keep their result and pay
+/// out at the fee migrate_config recorded from the old config; unsettled ones become Invalid and refundable
+/// Idempotent and permissionless: already-migrated epochs are left alone; the payer covers the extra rent
+pub fn migrate_epoch(
+    ctx: Context<MigrateEpoch>,
+    _asset_symbol: String,
+    _epoch_id: u64,
+) -> Result<()> {
+    let epoch = &ctx.accounts.epoch;
+
+    if epoch.data_len() == Epoch::LEN {
+        msg!("Epoch already migrated");
+        return Ok(());
+    }
+
+    let legacy = LegacyEpoch::from_account_data(&epoch.try_borrow_data()?)?;
+    let migrated = legacy.into_epoch(ctx.accounts.config.legacy_fee_bps);
+
+    grow_account(epoch, Epoch::LEN, &ctx.accounts.payer, &ctx.accounts.system_program)?;
+    migrated.try_serialize(&mut &mut epoch.try_borrow_mut_data()?[..])?;
+
+    msg!("Epoch migrated: {:?}", migrated.status);
+
+    Ok(())
+}
+
+/// Realloc a program account to `len`, topping its rent up from `payer`
+pub(crate) fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    len: usize,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+
+    account.realloc(len, false)?;
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String, epoch_id: u64)]
+pub struct MigrateEpoch<'info> {
+    /// CHECK: Epoch in either layout; owner and PDA checked here, discriminator and size in the handler
+    #[account(
+        mut,
+        seeds = [b"epoch", asset_symbol.as_bytes(), &epoch_id.to_be_bytes()],
+        bump,
+        owner = crate::ID,
+    )]
+    pub epoch: AccountInfo<'info>,
+
+    /// Must already be migrated, so its legacy_fee_bps snapshot exists
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Anyone can migrate an epoch (pays the extra rent)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -1,17 +1,53 @@
 pub mod initialize;
+pub mod update_config;
+pub mod transfer_admin;
+pub mod set_roles;
+pub mod governance;
 pub mod set_asset_feed;
+pub mod update_asset;
+pub mod init_vault;
+pub mod market;
 pub mod create_epoch;
 pub mod place_bet;
+pub mod cancel_bet;
 pub mod lock_epoch;
 pub mod close_epoch;
 pub mod claim;
+pub mod claim_many;
+pub mod refund;
+pub mod migrate_config;
+pub mod migrate_asset;
+pub mod migrate_bet;
+pub mod migrate_epoch;
+pub mod crank;
 pub mod pause;
+pub mod fund_tips;
+#[cfg(feature = "mock-oracle")]
+pub mod set_mock_price;
 
 pub use initialize::*;
+pub use update_config::*;
+pub use transfer_admin::*;
+pub use set_roles::*;
+pub use governance::*;
 pub use set_asset_feed::*;
+pub use update_asset::*;
+pub use init_vault::*;
+pub use market::*;
 pub use create_epoch::*;
 pub use place_bet::*;
+pub use cancel_bet::*;
 pub use lock_epoch::*;
 pub use close_epoch::*;
 pub use claim::*;
+pub use claim_many::*;
+pub use refund::*;
+pub use migrate_config::*;
+pub use migrate_asset::*;
+pub use migrate_bet::*;
+pub use migrate_epoch::*;
+pub use crank::*;
 pub use pause::*;
+pub use fund_tips::*;
+#[cfg(feature = "mock-oracle")]
+pub use set_mock_price::*;
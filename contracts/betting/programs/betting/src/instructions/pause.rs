@@ -3,6 +3,7 @@ use crate::state::GlobalConfig;
 use crate::errors::BettingError;
 
 /// Pause the protocol (only affects place_bet)
+/// Callable by the admin or the pauser
 pub fn pause(ctx: Context<Pause>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = true;
@@ -10,7 +11,7 @@ pub fn pause(ctx: Context<Pause>) -> Result<()> {
     Ok(())
 }
 
-/// Unpause the protocol
+/// Unpause the protocol (admin only)
 pub fn unpause(ctx: Context<Unpause>) -> Result<()> {
     let config = &mut ctx.accounts.config;
     config.paused = false;
@@ -24,11 +25,12 @@ pub struct Pause<'info> {
         mut,
         seeds = [b"config"],
         bump = config.bump,
-        has_one = admin @ BettingError::Unauthorized
+        constraint = authority.key() == config.admin
+            || authority.key() == config.pauser @ BettingError::Unauthorized
     )]
     pub config: Account<'info, GlobalConfig>,
 
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
@@ -2,25 +2,39 @@ use anchor_lang::prelude::*;
 use anchor_spl::token::{self, Token, TokenAccount, Transfer};
 use crate::state::{GlobalConfig, AssetConfig, Epoch, UserBet, BetSide, EpochStatus};
 use crate::errors::BettingError;
-use crate::events::BetPlaced;
+use crate::events::{BetPlaced, BetIncreased};
 
 /// Place a bet on Up or Down for a specific epoch
+/// Later bets from the same wallet add to either side, so a position can be hedged
 pub fn place_bet(
     ctx: Context<PlaceBet>,
     asset_symbol: String,
+    timeframe: String,
     epoch_id: u64,
     side: BetSide,
     amount: u64,
 ) -> Result<()> {
     let config = &ctx.accounts.config;
+    let asset_config = &ctx.accounts.asset_config;
     let epoch = &mut ctx.accounts.epoch;
     let user_bet = &mut ctx.accounts.user_bet;
 
     // Check protocol is not paused
     require!(!config.paused, BettingError::Paused);
 
+    // A bet account in an older layout must go through migrate_bet first
+    require!(
+        user_bet.to_account_info().data_len() == UserBet::LEN,
+        BettingError::BetNeedsMigration
+    );
+
     // Check amount is valid
     require!(amount > 0, BettingError::InvalidBetAmount);
+    require!(amount >= asset_config.min_bet, BettingError::BetTooSmall);
+    require!(
+        asset_config.max_bet == 0 || amount <= asset_config.max_bet,
+        BettingError::BetTooLarge
+    );
 
     // Check epoch status
     require!(
@@ -41,6 +55,24 @@ pub fn place_bet(
         BettingError::WrongMint
     );
 
+    // Check the user's position and the pool stay within their caps
+    let user_stake = user_bet.total_stake()?
+        .checked_add(amount)
+        .ok_or(BettingError::Overflow)?;
+    require!(
+        asset_config.max_user_stake_per_epoch == 0
+            || user_stake <= asset_config.max_user_stake_per_epoch,
+        BettingError::UserStakeLimitExceeded
+    );
+    let pool = epoch.sum_up
+        .checked_add(epoch.sum_down)
+        .and_then(|pool| pool.checked_add(amount))
+        .ok_or(BettingError::Overflow)?;
+    require!(
+        asset_config.max_epoch_pool == 0 || pool <= asset_config.max_epoch_pool,
+        BettingError::EpochPoolFull
+    );
+
     // Transfer USDC from user to vault
     let transfer_ctx = CpiContext::new(
         ctx.accounts.token_program.to_account_info(),
@@ -66,18 +98,49 @@ pub fn place_bet(
         }
     }
 
-    // Record user bet
-    user_bet.user = ctx.accounts.user.key();
-    user_bet.asset_symbol = asset_symbol.clone();
-    user_bet.epoch_id = epoch_id;
-    user_bet.side = side;
-    user_bet.stake = amount;
-    user_bet.claimed = false;
-    user_bet.bump = ctx.bumps.user_bet;
+    // An empty symbol means init_if_needed just created the bet
+    let is_top_up = !user_bet.asset_symbol.is_empty();
+    if !is_top_up {
+        user_bet.user = ctx.accounts.user.key();
+        user_bet.asset_symbol = asset_symbol.clone();
+        user_bet.timeframe = timeframe.clone();
+        user_bet.epoch_id = epoch_id;
+        user_bet.stake_up = 0;
+        user_bet.stake_down = 0;
+        user_bet.claimed = false;
+        user_bet.bump = ctx.bumps.user_bet;
+    }
+
+    // Record the stake on the chosen side
+    let side_stake = match side {
+        BetSide::Up => &mut user_bet.stake_up,
+        BetSide::Down => &mut user_bet.stake_down,
+    };
+    *side_stake = side_stake
+        .checked_add(amount)
+        .ok_or(BettingError::Overflow)?;
+
+    if is_top_up {
+        emit!(BetIncreased {
+            user: ctx.accounts.user.key(),
+            asset_symbol,
+            timeframe,
+            epoch_id,
+            side,
+            amount,
+            stake: user_bet.stake_on(side),
+            timestamp: clock.unix_timestamp,
+        });
+
+        msg!("Bet increased: {:?} +{} USDC (total {})", side, amount, user_bet.stake_on(side));
+
+        return Ok(());
+    }
 
     emit!(BetPlaced {
         user: ctx.accounts.user.key(),
         asset_symbol,
+        timeframe,
         epoch_id,
         side,
         amount,
@@ -90,16 +153,18 @@ pub fn place_bet(
 }
 
 #[derive(Accounts)]
-#[instruction(asset_symbol: String, epoch_id: u64, side: BetSide, amount: u64)]
+#[instruction(asset_symbol: String, timeframe: String, epoch_id: u64, side: BetSide, amount: u64)]
 pub struct PlaceBet<'info> {
+    /// User's position for this epoch (created on the first bet, added to afterwards)
     #[account(
-        init,
+        init_if_needed,
         payer = user,
         space = UserBet::LEN,
         seeds = [
             b"bet",
             user.key().as_ref(),
             asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
             &epoch_id.to_be_bytes()
         ],
         bump
@@ -111,6 +176,7 @@ pub struct PlaceBet<'info> {
         seeds = [
             b"epoch",
             asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
             &epoch_id.to_be_bytes()
         ],
         bump = epoch.bump,
@@ -134,6 +200,7 @@ pub struct PlaceBet<'info> {
         mut,
         seeds = [b"vault", asset_config.usdc_mint.as_ref(), asset_symbol.as_bytes()],
         bump,
+        address = asset_config.vault @ BettingError::InvalidVault,
         token::mint = asset_config.usdc_mint,
         token::authority = vault,
     )]
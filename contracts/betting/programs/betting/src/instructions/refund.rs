@@ -0,0 +1,120 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use crate::state::{AssetConfig, Epoch, UserBet};
+use crate::errors::BettingError;
+use crate::events::Refunded;
+
+/// Refund a bet in full for an invalid, tied or one-sided epoch
+pub fn refund(
+    ctx: Context<Refund>,
+    asset_symbol: String,
+    timeframe: String,
+    epoch_id: u64,
+) -> Result<()> {
+    let epoch = &ctx.accounts.epoch;
+    let user_bet = &mut ctx.accounts.user_bet;
+
+    // Check the epoch pays refunds rather than winnings
+    require!(epoch.is_refundable(), BettingError::NotRefundable);
+
+    // Check user hasn't already claimed or been refunded
+    require!(!user_bet.claimed, BettingError::AlreadyClaimed);
+
+    let amount = user_bet.total_stake()?;
+
+    // Transfer the full stake on both sides from vault to user (no fee)
+    let seeds = &[
+        b"vault",
+        ctx.accounts.asset_config.usdc_mint.as_ref(),
+        asset_symbol.as_bytes(),
+        &[ctx.bumps.vault],
+    ];
+    let signer_seeds = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_program.to_account_info(),
+        Transfer {
+            from: ctx.accounts.vault.to_account_info(),
+            to: ctx.accounts.user_ata.to_account_info(),
+            authority: ctx.accounts.vault.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token::transfer(transfer_ctx, amount)?;
+
+    // Mark as claimed so the stake cannot be refunded twice
+    user_bet.claimed = true;
+
+    let clock = Clock::get()?;
+    emit!(Refunded {
+        user: ctx.accounts.user.key(),
+        asset_symbol,
+        timeframe,
+        epoch_id,
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!("Refunded {} USDC", amount);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String, timeframe: String, epoch_id: u64)]
+pub struct Refund<'info> {
+    #[account(
+        mut,
+        seeds = [
+            b"bet",
+            user.key().as_ref(),
+            asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
+            &epoch_id.to_be_bytes()
+        ],
+        bump = user_bet.bump,
+        has_one = user @ BettingError::Unauthorized,
+        constraint = user_bet.to_account_info().data_len() == UserBet::LEN
+            @ BettingError::BetNeedsMigration,
+    )]
+    pub user_bet: Account<'info, UserBet>,
+
+    #[account(
+        seeds = [
+            b"epoch",
+            asset_symbol.as_bytes(),
+            timeframe.as_bytes(),
+            &epoch_id.to_be_bytes()
+        ],
+        bump = epoch.bump,
+    )]
+    pub epoch: Account<'info, Epoch>,
+
+    #[account(
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    /// Vault token account holding USDC for this asset
+    #[account(
+        mut,
+        seeds = [b"vault", asset_config.usdc_mint.as_ref(), asset_symbol.as_bytes()],
+        bump,
+        address = asset_config.vault @ BettingError::InvalidVault,
+        token::mint = asset_config.usdc_mint,
+        token::authority = vault,
+    )]
+    pub vault: Account<'info, TokenAccount>,
+
+    /// User's USDC token account
+    #[account(
+        mut,
+        token::mint = asset_config.usdc_mint,
+    )]
+    pub user_ata: Account<'info, TokenAccount>,
+
+    pub user: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+}
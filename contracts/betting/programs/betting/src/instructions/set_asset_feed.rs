@@ -1,8 +1,11 @@
 use anchor_lang::prelude::*;
 use crate::state::{GlobalConfig, AssetConfig};
 use crate::errors::BettingError;
+use crate::oracle::PriceStatus;
 
 /// Set or update the Pyth price feed for a specific asset
+/// Changing an existing asset's feed or mint goes through queue_change while a timelock is set,
+/// and the mint can no longer change once init_vault has created the asset's vault
 pub fn set_asset_feed(
     ctx: Context<SetAssetFeed>,
     asset_symbol: String,
@@ -16,12 +19,40 @@ pub fn set_asset_feed(
 
     let asset_config = &mut ctx.accounts.asset_config;
 
+    // Swapping the feed or mint of a live asset must wait out the timelock
+    if !asset_config.asset_symbol.is_empty() && ctx.accounts.config.timelock_secs > 0 {
+        require!(
+            pyth_price_account == asset_config.pyth_price_account
+                && usdc_mint == asset_config.usdc_mint,
+            BettingError::TimelockRequired
+        );
+    }
+
+    // Stakes sit in the vault for the current mint, so the mint is fixed once it exists
+    require!(
+        usdc_mint == asset_config.usdc_mint || asset_config.vault == Pubkey::default(),
+        BettingError::MintLocked
+    );
+
+    // Default oracle guards on first configuration; tune later with update_asset
+    if asset_config.asset_symbol.is_empty() {
+        asset_config.max_price_age_secs = AssetConfig::DEFAULT_MAX_PRICE_AGE_SECS;
+        asset_config.max_conf_bps = AssetConfig::DEFAULT_MAX_CONF_BPS;
+        asset_config.required_status = PriceStatus::Trading;
+        asset_config.quorum_tolerance_bps = AssetConfig::DEFAULT_QUORUM_TOLERANCE_BPS;
+    }
+
     asset_config.asset_symbol = asset_symbol.clone();
     asset_config.pyth_price_account = pyth_price_account;
     asset_config.usdc_mint = usdc_mint;
-    asset_config.active_epoch_id = 0;
     asset_config.bump = ctx.bumps.asset_config;
 
+    // The new primary account must not repeat one of the secondary sources
+    require!(
+        !asset_config.has_duplicate_oracle_sources(),
+        BettingError::DuplicateOracleSource
+    );
+
     msg!("Asset feed configured: {}", asset_symbol);
     msg!("Pyth price account: {}", pyth_price_account);
     msg!("USDC mint: {}", usdc_mint);
@@ -34,7 +65,7 @@ pub fn set_asset_feed(
 pub struct SetAssetFeed<'info> {
     #[account(
         init_if_needed,
-        payer = admin,
+        payer = authority,
         space = AssetConfig::LEN,
         seeds = [b"asset", asset_symbol.as_bytes()],
         bump
@@ -44,12 +75,14 @@ pub struct SetAssetFeed<'info> {
     #[account(
         seeds = [b"config"],
         bump = config.bump,
-        has_one = admin @ BettingError::Unauthorized
+        constraint = authority.key() == config.admin
+            || authority.key() == config.asset_manager @ BettingError::Unauthorized
     )]
     pub config: Account<'info, GlobalConfig>,
 
+    /// Admin or asset manager
     #[account(mut)]
-    pub admin: Signer<'info>,
+    pub authority: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
@@ -0,0 +1,57 @@
+use anchor_lang::prelude::*;
+use crate::state::{GlobalConfig, AssetConfig, MockPrice};
+use crate::errors::BettingError;
+
+/// Write a price into the asset's mock feed (mock-oracle builds only)
+/// Point the asset at the feed with update_asset(oracle_kind = Mock, account = mock_price PDA)
+pub fn set_mock_price(
+    ctx: Context<SetMockPrice>,
+    asset_symbol: String,
+    price: i64,
+    conf: u64,
+    expo: i32,
+    publish_time: i64,
+) -> Result<()> {
+    let mock_price = &mut ctx.accounts.mock_price;
+
+    mock_price.price = price;
+    mock_price.conf = conf;
+    mock_price.expo = expo;
+    mock_price.publish_time = publish_time;
+    mock_price.bump = ctx.bumps.mock_price;
+
+    msg!("Mock price set for {}: {} (expo {}) at {}", asset_symbol, price, expo, publish_time);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String)]
+pub struct SetMockPrice<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = MockPrice::LEN,
+        seeds = [b"mock_price", asset_symbol.as_bytes()],
+        bump
+    )]
+    pub mock_price: Account<'info, MockPrice>,
+
+    #[account(
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::GlobalConfig;
+use crate::errors::BettingError;
+use crate::events::RolesUpdated;
+
+/// Assign the pauser, fee manager and asset manager roles (only provided roles change)
+pub fn set_roles(
+    ctx: Context<SetRoles>,
+    pauser: Option<Pubkey>,
+    fee_manager: Option<Pubkey>,
+    asset_manager: Option<Pubkey>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    if let Some(pauser) = pauser {
+        config.pauser = pauser;
+        msg!("Pauser: {}", pauser);
+    }
+
+    if let Some(fee_manager) = fee_manager {
+        config.fee_manager = fee_manager;
+        msg!("Fee manager: {}", fee_manager);
+    }
+
+    if let Some(asset_manager) = asset_manager {
+        config.asset_manager = asset_manager;
+        msg!("Asset manager: {}", asset_manager);
+    }
+
+    emit!(RolesUpdated {
+        pauser: config.pauser,
+        fee_manager: config.fee_manager,
+        asset_manager: config.asset_manager,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetRoles<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
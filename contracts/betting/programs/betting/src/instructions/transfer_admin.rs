@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use crate::state::GlobalConfig;
+use crate::errors::BettingError;
+use crate::events::{AdminProposed, AdminTransferred, RolesUpdated};
+
+/// Propose a new admin; the transfer completes once they call accept_admin
+/// Proposing the default pubkey cancels a pending transfer
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.pending_admin = new_admin;
+
+    emit!(AdminProposed {
+        admin: config.admin,
+        pending_admin: new_admin,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Admin proposed: {}", new_admin);
+    Ok(())
+}
+
+/// Accept a pending admin transfer
+/// Roles the outgoing admin still holds move with it; roles delegated to others stay put
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config: &mut GlobalConfig = &mut ctx.accounts.config;
+    let old_admin = config.admin;
+    let new_admin = config.pending_admin;
+    let now = Clock::get()?.unix_timestamp;
+
+    config.admin = new_admin;
+    config.pending_admin = Pubkey::default();
+
+    let mut roles_moved = false;
+    for role in [&mut config.pauser, &mut config.fee_manager, &mut config.asset_manager] {
+        if *role == old_admin {
+            *role = new_admin;
+            roles_moved = true;
+        }
+    }
+
+    emit!(AdminTransferred {
+        old_admin,
+        new_admin,
+        timestamp: now,
+    });
+
+    if roles_moved {
+        emit!(RolesUpdated {
+            pauser: config.pauser,
+            fee_manager: config.fee_manager,
+            asset_manager: config.asset_manager,
+            timestamp: now,
+        });
+    }
+
+    msg!("Admin transferred: {} -> {}", old_admin, new_admin);
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin != Pubkey::default()
+            && config.pending_admin == pending_admin.key() @ BettingError::NotPendingAdmin
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub pending_admin: Signer<'info>,
+}
@@ -0,0 +1,203 @@
+use anchor_lang::prelude::*;
+use crate::state::{GlobalConfig, AssetConfig, AssetOracleChange, OneSidedPolicy};
+use crate::errors::BettingError;
+use crate::oracle::{OracleKind, OracleSource, PriceStatus};
+use crate::oracle::quorum::QuorumRule;
+
+/// Update settlement policies, oracle guards, oracle sources, grid offset and bet limits for an asset (only provided fields change)
+/// Oracle source, price guard and one-sided policy changes must go through queue_change while a timelock is set
+#[allow(clippy::too_many_arguments)]
+pub fn update_asset(
+    ctx: Context<UpdateAsset>,
+    asset_symbol: String,
+    one_sided_policy: Option<OneSidedPolicy>,
+    max_price_age_secs: Option<u32>,
+    max_conf_bps: Option<u16>,
+    required_status: Option<PriceStatus>,
+    oracle_kind: Option<OracleKind>,
+    feed_id: Option<[u8; 32]>,
+    secondary_oracles: Option<Vec<OracleSource>>,
+    quorum_rule: Option<QuorumRule>,
+    quorum_tolerance_bps: Option<u16>,
+    grid_offset_secs: Option<u32>,
+    min_bet: Option<u64>,
+    max_bet: Option<u64>,
+    max_user_stake_per_epoch: Option<u64>,
+    max_epoch_pool: Option<u64>,
+) -> Result<()> {
+    let asset_config = &mut ctx.accounts.asset_config;
+
+    let change = AssetOracleChange {
+        asset_symbol: asset_symbol.clone(),
+        pyth_price_account: None,
+        usdc_mint: None,
+        oracle_kind,
+        feed_id,
+        secondary_oracles,
+        max_price_age_secs,
+        max_conf_bps,
+        required_status,
+        quorum_rule,
+        quorum_tolerance_bps,
+        one_sided_policy,
+    };
+    require!(
+        ctx.accounts.config.timelock_secs == 0 || !change.is_sensitive(asset_config),
+        BettingError::TimelockRequired
+    );
+    apply_asset_oracle_change(asset_config, &change)?;
+
+    // Markets fold the offset into their grid origin, so it can only change before the first one
+    if let Some(grid_offset_secs) = grid_offset_secs {
+        require!(
+            asset_config.market_count == 0 || grid_offset_secs == asset_config.grid_offset_secs,
+            BettingError::GridOffsetLocked
+        );
+        asset_config.grid_offset_secs = grid_offset_secs;
+        msg!("Grid offset: {}s", grid_offset_secs);
+    }
+
+    // Bet limits apply to later place_bet calls; a limit of 0 is off
+    if let Some(min_bet) = min_bet {
+        asset_config.min_bet = min_bet;
+        msg!("Min bet: {}", min_bet);
+    }
+
+    if let Some(max_bet) = max_bet {
+        asset_config.max_bet = max_bet;
+        msg!("Max bet: {}", max_bet);
+    }
+
+    if let Some(max_user_stake_per_epoch) = max_user_stake_per_epoch {
+        asset_config.max_user_stake_per_epoch = max_user_stake_per_epoch;
+        msg!("Max user stake per epoch: {}", max_user_stake_per_epoch);
+    }
+
+    if let Some(max_epoch_pool) = max_epoch_pool {
+        asset_config.max_epoch_pool = max_epoch_pool;
+        msg!("Max epoch pool: {}", max_epoch_pool);
+    }
+
+    require!(
+        asset_config.max_bet == 0 || asset_config.min_bet <= asset_config.max_bet,
+        BettingError::InvalidBetLimits
+    );
+
+    msg!("Asset updated: {}", asset_symbol);
+
+    Ok(())
+}
+
+/// Validate and apply an oracle, price guard, one-sided policy or mint change to an existing asset
+pub(crate) fn apply_asset_oracle_change(
+    asset_config: &mut AssetConfig,
+    change: &AssetOracleChange,
+) -> Result<()> {
+    if let Some(pyth_price_account) = change.pyth_price_account {
+        asset_config.pyth_price_account = pyth_price_account;
+        msg!("Pyth price account: {}", pyth_price_account);
+    }
+
+    if let Some(usdc_mint) = change.usdc_mint {
+        // Stakes sit in the vault for the current mint, so the mint is fixed once it exists
+        require!(
+            usdc_mint == asset_config.usdc_mint || asset_config.vault == Pubkey::default(),
+            BettingError::MintLocked
+        );
+        asset_config.usdc_mint = usdc_mint;
+        msg!("USDC mint: {}", usdc_mint);
+    }
+
+    if let Some(oracle_kind) = change.oracle_kind {
+        asset_config.oracle_kind = oracle_kind;
+        msg!("Oracle kind: {:?}", oracle_kind);
+    }
+
+    if let Some(feed_id) = change.feed_id {
+        asset_config.feed_id = feed_id;
+        msg!("Feed id: {:?}", feed_id);
+    }
+
+    if let Some(max_price_age_secs) = change.max_price_age_secs {
+        require!(max_price_age_secs > 0, BettingError::InvalidOracleGuard);
+        asset_config.max_price_age_secs = max_price_age_secs;
+        msg!("Max price age: {}s", max_price_age_secs);
+    }
+
+    if let Some(max_conf_bps) = change.max_conf_bps {
+        require!(
+            max_conf_bps > 0 && max_conf_bps <= 10_000,
+            BettingError::InvalidOracleGuard
+        );
+        asset_config.max_conf_bps = max_conf_bps;
+        msg!("Max confidence: {} bps", max_conf_bps);
+    }
+
+    if let Some(required_status) = change.required_status {
+        asset_config.required_status = required_status;
+        msg!("Required status: {:?}", required_status);
+    }
+
+    if let Some(secondary_oracles) = &change.secondary_oracles {
+        require!(
+            secondary_oracles.len() <= AssetConfig::MAX_SECONDARY_ORACLES,
+            BettingError::TooManyOracles
+        );
+        let mut slots = [asset_config.primary_oracle(); AssetConfig::MAX_SECONDARY_ORACLES];
+        slots[..secondary_oracles.len()].copy_from_slice(secondary_oracles);
+        asset_config.secondary_oracles = slots;
+        asset_config.secondary_oracle_count = secondary_oracles.len() as u8;
+        msg!("Secondary oracles: {}", secondary_oracles.len());
+    }
+
+    // A pull oracle without a feed id would never match any update
+    for source in asset_config.oracle_sources() {
+        require!(
+            source.kind != OracleKind::PythPull || source.feed_id != [0u8; 32],
+            BettingError::InvalidOracleGuard
+        );
+    }
+
+    require!(
+        !asset_config.has_duplicate_oracle_sources(),
+        BettingError::DuplicateOracleSource
+    );
+
+    if let Some(quorum_rule) = change.quorum_rule {
+        asset_config.quorum_rule = quorum_rule;
+        msg!("Quorum rule: {:?}", quorum_rule);
+    }
+
+    if let Some(quorum_tolerance_bps) = change.quorum_tolerance_bps {
+        require!(quorum_tolerance_bps <= 10_000, BettingError::InvalidOracleGuard);
+        asset_config.quorum_tolerance_bps = quorum_tolerance_bps;
+        msg!("Quorum tolerance: {} bps", quorum_tolerance_bps);
+    }
+
+    if let Some(one_sided_policy) = change.one_sided_policy {
+        asset_config.one_sided_policy = one_sided_policy;
+        msg!("One-sided policy: {:?}", one_sided_policy);
+    }
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(asset_symbol: String)]
+pub struct UpdateAsset<'info> {
+    #[account(
+        mut,
+        seeds = [b"asset", asset_symbol.as_bytes()],
+        bump = asset_config.bump,
+    )]
+    pub asset_config: Account<'info, AssetConfig>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    pub admin: Signer<'info>,
+}
@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use crate::state::{GlobalConfig, ConfigChange};
+use crate::errors::BettingError;
+use crate::events::ConfigUpdated;
+
+/// Update protocol parameters (only provided fields change)
+/// The fee manager may only change the fees, keeper share and settle tip; everything else needs the admin
+/// Fee hikes and timelock reductions must go through queue_change while a timelock is set
+#[allow(clippy::too_many_arguments)]
+pub fn update_config(
+    ctx: Context<UpdateConfig>,
+    fee_bps: Option<u16>,
+    settle_tip_lamports: Option<u64>,
+    cutoff_secs: Option<u32>,
+    epoch_length_secs: Option<u32>,
+    treasury: Option<Pubkey>,
+    timelock_secs: Option<u32>,
+    keeper_fee_bps: Option<u16>,
+    max_future_epochs: Option<u16>,
+    cancel_fee_bps: Option<u16>,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let change = ConfigChange {
+        fee_bps,
+        settle_tip_lamports,
+        cutoff_secs,
+        epoch_length_secs,
+        treasury,
+        timelock_secs,
+        keeper_fee_bps,
+        max_future_epochs,
+        cancel_fee_bps,
+    };
+
+    if ctx.accounts.authority.key() != config.admin {
+        require!(
+            cutoff_secs.is_none()
+                && epoch_length_secs.is_none()
+                && treasury.is_none()
+                && timelock_secs.is_none()
+                && max_future_epochs.is_none(),
+            BettingError::Unauthorized
+        );
+    }
+
+    require!(
+        config.timelock_secs == 0 || !change.is_sensitive(config),
+        BettingError::TimelockRequired
+    );
+
+    apply_config_change(config, &change, Clock::get()?.unix_timestamp)
+}
+
+/// Validate and apply a config change, emitting ConfigUpdated
+/// Epoch length and cutoff are only defaults for new markets; existing markets change through update_market
+pub(crate) fn apply_config_change(
+    config: &mut GlobalConfig,
+    change: &ConfigChange,
+    now: i64,
+) -> Result<()> {
+    let old_fee_bps = config.fee_bps;
+    let old_settle_tip_lamports = config.settle_tip_lamports;
+    let old_cutoff_secs = config.cutoff_secs;
+    let old_epoch_length_secs = config.epoch_length_secs;
+    let old_treasury = config.treasury;
+    let old_timelock_secs = config.timelock_secs;
+    let old_keeper_fee_bps = config.keeper_fee_bps;
+    let old_max_future_epochs = config.max_future_epochs;
+    let old_cancel_fee_bps = config.cancel_fee_bps;
+
+    if let Some(fee_bps) = change.fee_bps {
+        require!(fee_bps <= GlobalConfig::MAX_FEE_BPS, BettingError::FeeTooHigh);
+        config.fee_bps = fee_bps;
+        msg!("Fee: {} bps", fee_bps);
+    }
+
+    if let Some(keeper_fee_bps) = change.keeper_fee_bps {
+        require!(keeper_fee_bps <= 10_000, BettingError::KeeperFeeTooHigh);
+        config.keeper_fee_bps = keeper_fee_bps;
+        msg!("Keeper fee: {} bps of the fee", keeper_fee_bps);
+    }
+
+    if let Some(cancel_fee_bps) = change.cancel_fee_bps {
+        require!(cancel_fee_bps <= GlobalConfig::MAX_FEE_BPS, BettingError::FeeTooHigh);
+        config.cancel_fee_bps = cancel_fee_bps;
+        msg!("Cancel fee: {} bps", cancel_fee_bps);
+    }
+
+    if let Some(settle_tip_lamports) = change.settle_tip_lamports {
+        config.settle_tip_lamports = settle_tip_lamports;
+        msg!("Settle tip: {} lamports", settle_tip_lamports);
+    }
+
+    if let Some(treasury) = change.treasury {
+        config.treasury = treasury;
+        msg!("Treasury: {}", treasury);
+    }
+
+    if let Some(max_future_epochs) = change.max_future_epochs {
+        config.max_future_epochs = max_future_epochs;
+        msg!("Max future epochs: {}", max_future_epochs);
+    }
+
+    if let Some(timelock_secs) = change.timelock_secs {
+        config.timelock_secs = timelock_secs;
+        msg!("Timelock: {}s", timelock_secs);
+    }
+
+    if let Some(epoch_length_secs) = change.epoch_length_secs {
+        require!(epoch_length_secs > 0, BettingError::InvalidEpochLength);
+        config.epoch_length_secs = epoch_length_secs;
+        msg!("Default epoch length: {}s", epoch_length_secs);
+    }
+
+    if let Some(cutoff_secs) = change.cutoff_secs {
+        config.cutoff_secs = cutoff_secs;
+        msg!("Default cutoff: {}s", cutoff_secs);
+    }
+
+    require!(
+        config.cutoff_secs < config.epoch_length_secs,
+        BettingError::InvalidCutoff
+    );
+
+    emit!(ConfigUpdated {
+        old_fee_bps,
+        new_fee_bps: config.fee_bps,
+        old_settle_tip_lamports,
+        new_settle_tip_lamports: config.settle_tip_lamports,
+        old_cutoff_secs,
+        new_cutoff_secs: config.cutoff_secs,
+        old_epoch_length_secs,
+        new_epoch_length_secs: config.epoch_length_secs,
+        old_treasury,
+        new_treasury: config.treasury,
+        old_timelock_secs,
+        new_timelock_secs: config.timelock_secs,
+        old_keeper_fee_bps,
+        new_keeper_fee_bps: config.keeper_fee_bps,
+        old_max_future_epochs,
+        new_max_future_epochs: config.max_future_epochs,
+        old_cancel_fee_bps,
+        new_cancel_fee_bps: config.cancel_fee_bps,
+        timestamp: now,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.admin
+            || authority.key() == config.fee_manager @ BettingError::Unauthorized
+    )]
+    pub config: Account<'info, GlobalConfig>,
+
+    /// Admin or fee manager
+    pub authority: Signer<'info>,
+}
@@ -4,21 +4,56 @@ pub mod state;
 pub mod errors;
 pub mod events;
 pub mod instructions;
+pub mod oracle;
 
 use instructions::*;
-use state::BetSide;
+use state::{BetSide, OneSidedPolicy, GovernanceChange};
+use oracle::{OracleKind, OracleSource, PriceStatus};
+use oracle::quorum::QuorumRule;
 
 declare_id!("11111111111111111111111111111111");
 
+/// Anchor's #[program] ignores #[cfg] on single instructions, so mock-oracle builds
+/// splice set_mock_price into the module before the attribute expands
+#[cfg(feature = "mock-oracle")]
+macro_rules! with_mock_instructions {
+    ($(#[$attr:meta])* pub mod $name:ident { $($body:tt)* }) => {
+        $(#[$attr])*
+        pub mod $name {
+            $($body)*
+
+            /// Write a price into an asset's mock feed
+            pub fn set_mock_price(
+                ctx: Context<SetMockPrice>,
+                asset_symbol: String,
+                price: i64,
+                conf: u64,
+                expo: i32,
+                publish_time: i64,
+            ) -> Result<()> {
+                instructions::set_mock_price(ctx, asset_symbol, price, conf, expo, publish_time)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "mock-oracle"))]
+macro_rules! with_mock_instructions {
+    ($($item:tt)*) => { $($item)* };
+}
+
+with_mock_instructions! {
 #[program]
 pub mod betting {
     use super::*;
 
     /// Initialize the global betting protocol configuration
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize(
         ctx: Context<Initialize>,
         admin: Pubkey,
         treasury: Pubkey,
+        pyth_program: Pubkey,
         fee_bps: u16,
         settle_tip_lamports: u64,
         cutoff_secs: u32,
@@ -28,6 +63,7 @@ pub mod betting {
             ctx,
             admin,
             treasury,
+            pyth_program,
             fee_bps,
             settle_tip_lamports,
             cutoff_secs,
@@ -35,6 +71,69 @@ pub mod betting {
         )
     }
 
+    /// Update protocol and cancellation fees, settle tip, default market timing, treasury, timelock or pre-creation window
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_config(
+        ctx: Context<UpdateConfig>,
+        fee_bps: Option<u16>,
+        settle_tip_lamports: Option<u64>,
+        cutoff_secs: Option<u32>,
+        epoch_length_secs: Option<u32>,
+        treasury: Option<Pubkey>,
+        timelock_secs: Option<u32>,
+        keeper_fee_bps: Option<u16>,
+        max_future_epochs: Option<u16>,
+        cancel_fee_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::update_config(
+            ctx,
+            fee_bps,
+            settle_tip_lamports,
+            cutoff_secs,
+            epoch_length_secs,
+            treasury,
+            timelock_secs,
+            keeper_fee_bps,
+            max_future_epochs,
+            cancel_fee_bps,
+        )
+    }
+
+    /// Queue a fee hike, timelock reduction, oracle swap or market fee hike behind the timelock
+    pub fn queue_change(ctx: Context<QueueChange>, change: GovernanceChange) -> Result<()> {
+        instructions::queue_change(ctx, change)
+    }
+
+    /// Apply a queued change once its timelock has elapsed
+    pub fn execute_change(ctx: Context<ExecuteChange>, change_id: u64) -> Result<()> {
+        instructions::execute_change(ctx, change_id)
+    }
+
+    /// Cancel a queued change before it executes
+    pub fn cancel_change(ctx: Context<CancelChange>, change_id: u64) -> Result<()> {
+        instructions::cancel_change(ctx, change_id)
+    }
+
+    /// Propose a new admin (two-step transfer)
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin(ctx, new_admin)
+    }
+
+    /// Accept a pending admin transfer
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin(ctx)
+    }
+
+    /// Assign the pauser, fee manager and asset manager roles
+    pub fn set_roles(
+        ctx: Context<SetRoles>,
+        pauser: Option<Pubkey>,
+        fee_manager: Option<Pubkey>,
+        asset_manager: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_roles(ctx, pauser, fee_manager, asset_manager)
+    }
+
     /// Set or update the Pyth price feed for a specific asset
     pub fn set_asset_feed(
         ctx: Context<SetAssetFeed>,
@@ -45,50 +144,213 @@ pub mod betting {
         instructions::set_asset_feed(ctx, asset_symbol, pyth_price_account, usdc_mint)
     }
 
-    /// Create a new betting epoch for an asset
-    pub fn create_epoch(
-        ctx: Context<CreateEpoch>,
+    /// Update settlement policies, oracle guards, oracle sources, grid offset and bet limits for an asset
+    #[allow(clippy::too_many_arguments)]
+    pub fn update_asset(
+        ctx: Context<UpdateAsset>,
+        asset_symbol: String,
+        one_sided_policy: Option<OneSidedPolicy>,
+        max_price_age_secs: Option<u32>,
+        max_conf_bps: Option<u16>,
+        required_status: Option<PriceStatus>,
+        oracle_kind: Option<OracleKind>,
+        feed_id: Option<[u8; 32]>,
+        secondary_oracles: Option<Vec<OracleSource>>,
+        quorum_rule: Option<QuorumRule>,
+        quorum_tolerance_bps: Option<u16>,
+        grid_offset_secs: Option<u32>,
+        min_bet: Option<u64>,
+        max_bet: Option<u64>,
+        max_user_stake_per_epoch: Option<u64>,
+        max_epoch_pool: Option<u64>,
+    ) -> Result<()> {
+        instructions::update_asset(
+            ctx,
+            asset_symbol,
+            one_sided_policy,
+            max_price_age_secs,
+            max_conf_bps,
+            required_status,
+            oracle_kind,
+            feed_id,
+            secondary_oracles,
+            quorum_rule,
+            quorum_tolerance_bps,
+            grid_offset_secs,
+            min_bet,
+            max_bet,
+            max_user_stake_per_epoch,
+            max_epoch_pool,
+        )
+    }
+
+    /// Create the USDC vault for an asset's configured mint
+    pub fn init_vault(ctx: Context<InitVault>, asset_symbol: String) -> Result<()> {
+        instructions::init_vault(ctx, asset_symbol)
+    }
+
+    /// Open a timeframe market for an asset with its own epoch length, cutoff and fee override
+    pub fn create_market(
+        ctx: Context<CreateMarket>,
+        asset_symbol: String,
+        timeframe: String,
+        epoch_length_secs: Option<u32>,
+        cutoff_secs: Option<u32>,
+        fee_bps: Option<u16>,
+    ) -> Result<()> {
+        instructions::create_market(ctx, asset_symbol, timeframe, epoch_length_secs, cutoff_secs, fee_bps)
+    }
+
+    /// Update a market's epoch length, cutoff or fee override
+    pub fn update_market(
+        ctx: Context<UpdateMarket>,
         asset_symbol: String,
+        timeframe: String,
+        epoch_length_secs: Option<u32>,
+        cutoff_secs: Option<u32>,
+        fee_bps: Option<u16>,
+        clear_fee_bps: bool,
     ) -> Result<()> {
-        instructions::create_epoch(ctx, asset_symbol)
+        instructions::update_market(
+            ctx,
+            asset_symbol,
+            timeframe,
+            epoch_length_secs,
+            cutoff_secs,
+            fee_bps,
+            clear_fee_bps,
+        )
     }
 
-    /// Place a bet on Up or Down for a specific epoch
+    /// Create a new betting epoch for a market
+    pub fn create_epoch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreateEpoch<'info>>,
+        asset_symbol: String,
+        timeframe: String,
+    ) -> Result<()> {
+        instructions::create_epoch(ctx, asset_symbol, timeframe)
+    }
+
+    /// Pre-create an epoch up to max_future_epochs grid slots ahead
+    pub fn create_epoch_at(
+        ctx: Context<CreateEpochAt>,
+        asset_symbol: String,
+        timeframe: String,
+        epoch_id: u64,
+    ) -> Result<()> {
+        instructions::create_epoch_at(ctx, asset_symbol, timeframe, epoch_id)
+    }
+
+    /// Place a bet on Up or Down for a specific epoch, adding to any existing position
     pub fn place_bet(
         ctx: Context<PlaceBet>,
         asset_symbol: String,
+        timeframe: String,
         epoch_id: u64,
         side: BetSide,
         amount: u64,
     ) -> Result<()> {
-        instructions::place_bet(ctx, asset_symbol, epoch_id, side, amount)
+        instructions::place_bet(ctx, asset_symbol, timeframe, epoch_id, side, amount)
+    }
+
+    /// Cancel a bet before cutoff, refunding the stake minus the cancellation fee
+    pub fn cancel_bet(
+        ctx: Context<CancelBet>,
+        asset_symbol: String,
+        timeframe: String,
+        epoch_id: u64,
+    ) -> Result<()> {
+        instructions::cancel_bet(ctx, asset_symbol, timeframe, epoch_id)
     }
 
     /// Lock an epoch once cutoff time is reached
     pub fn lock_epoch(
         ctx: Context<LockEpoch>,
         asset_symbol: String,
+        timeframe: String,
         epoch_id: u64,
     ) -> Result<()> {
-        instructions::lock_epoch(ctx, asset_symbol, epoch_id)
+        instructions::lock_epoch(ctx, asset_symbol, timeframe, epoch_id)
     }
 
     /// Close and settle an epoch using Pyth price oracle
-    pub fn close_epoch(
-        ctx: Context<CloseEpoch>,
+    pub fn close_epoch<'info>(
+        ctx: Context<'_, '_, '_, 'info, CloseEpoch<'info>>,
         asset_symbol: String,
+        timeframe: String,
         epoch_id: u64,
     ) -> Result<()> {
-        instructions::close_epoch(ctx, asset_symbol, epoch_id)
+        instructions::close_epoch(ctx, asset_symbol, timeframe, epoch_id)
     }
 
     /// Claim winnings for a settled epoch
     pub fn claim(
         ctx: Context<Claim>,
         asset_symbol: String,
+        timeframe: String,
+        epoch_id: u64,
+    ) -> Result<()> {
+        instructions::claim(ctx, asset_symbol, timeframe, epoch_id)
+    }
+
+    /// Claim winnings for many settled epochs of one asset in a single transfer
+    pub fn claim_many<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimMany<'info>>,
+        asset_symbol: String,
+    ) -> Result<()> {
+        instructions::claim_many(ctx, asset_symbol)
+    }
+
+    /// Refund a bet in full for an invalid, tied or one-sided epoch
+    pub fn refund(
+        ctx: Context<Refund>,
+        asset_symbol: String,
+        timeframe: String,
+        epoch_id: u64,
+    ) -> Result<()> {
+        instructions::refund(ctx, asset_symbol, timeframe, epoch_id)
+    }
+
+    /// Upgrade the global config from before roles and timelocks to the current layout
+    pub fn migrate_config(ctx: Context<MigrateConfig>, pyth_program: Pubkey) -> Result<()> {
+        instructions::migrate_config(ctx, pyth_program)
+    }
+
+    /// Upgrade an asset config from before oracle sources and markets to the current layout
+    pub fn migrate_asset(ctx: Context<MigrateAsset>, asset_symbol: String) -> Result<()> {
+        instructions::migrate_asset(ctx, asset_symbol)
+    }
+
+    /// Upgrade a bet account from before timeframe markets to the hedged stake layout
+    pub fn migrate_bet(
+        ctx: Context<MigrateBet>,
+        asset_symbol: String,
+        epoch_id: u64,
+    ) -> Result<()> {
+        instructions::migrate_bet(ctx, asset_symbol, epoch_id)
+    }
+
+    /// Upgrade an epoch account from before timeframe markets to the current layout
+    pub fn migrate_epoch(
+        ctx: Context<MigrateEpoch>,
+        asset_symbol: String,
         epoch_id: u64,
     ) -> Result<()> {
-        instructions::claim(ctx, asset_symbol, epoch_id)
+        instructions::migrate_epoch(ctx, asset_symbol, epoch_id)
+    }
+
+    /// Deposit lamports into the settle tip jar
+    pub fn fund_tips(ctx: Context<FundTips>, amount: u64) -> Result<()> {
+        instructions::fund_tips(ctx, amount)
+    }
+
+    /// Lock, settle and open epochs for a market as they come due
+    pub fn crank<'info>(
+        ctx: Context<'_, '_, '_, 'info, Crank<'info>>,
+        asset_symbol: String,
+        timeframe: String,
+    ) -> Result<()> {
+        instructions::crank(ctx, asset_symbol, timeframe)
     }
 
     /// Pause the protocol (only affects place_bet)
@@ -101,3 +363,4 @@ pub mod betting {
         instructions::unpause(ctx)
     }
 }
+}
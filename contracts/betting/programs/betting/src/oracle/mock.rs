@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use super::{OracleError, OraclePrice, PriceStatus};
+use crate::state::MockPrice;
+
+/// Decode a MockPrice PDA owned by this program
+pub fn load_mock_price(account: &AccountInfo) -> std::result::Result<OraclePrice, OracleError> {
+    if account.owner != &crate::ID {
+        return Err(OracleError::WrongOwner);
+    }
+
+    let data = account.try_borrow_data().map_err(|_| OracleError::AccountTooSmall)?;
+    let mock = MockPrice::try_deserialize(&mut &data[..]).map_err(|_| OracleError::BadAccountType)?;
+
+    Ok(OraclePrice {
+        price: mock.price,
+        conf: mock.conf,
+        expo: mock.expo,
+        status: PriceStatus::Trading,
+        publish_time: mock.publish_time,
+    })
+}
@@ -0,0 +1,202 @@
+use anchor_lang::prelude::*;
+use crate::state::AssetConfig;
+
+pub mod pyth;
+pub mod pyth_pull;
+pub mod switchboard;
+pub mod quorum;
+#[cfg(feature = "mock-oracle")]
+pub mod mock;
+
+/// Latest a price may be published after the time it should describe
+/// A price from after the reference time reflects moves the epoch never covered, so every source
+/// gets only this small allowance for slot timing; max_price_age_secs bounds prices from before it
+pub const MAX_PUBLISH_DELAY_SECS: i64 = 5;
+
+/// Reasons an oracle account can fail to produce a usable price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OracleError {
+    /// Account is not owned by the configured oracle program
+    WrongOwner,
+    /// Account data is shorter than the expected layout
+    AccountTooSmall,
+    /// Magic number does not match
+    BadMagic,
+    /// Unsupported account layout version
+    BadVersion,
+    /// Account is not a price account
+    BadAccountType,
+    /// Pull update was only partially verified
+    InsufficientVerification,
+    /// Pull update is for a different price feed
+    FeedIdMismatch,
+    /// Aggregate status does not match the asset's required status
+    NotTrading,
+    /// Aggregate price is zero or negative
+    NonPositivePrice,
+    /// Price does not fit the supported mantissa/exponent range
+    PriceOutOfRange,
+    /// Price was published too far from the reference time
+    StalePrice,
+    /// Confidence interval is too wide relative to the price
+    ConfidenceTooWide,
+    /// No account was supplied for a configured oracle source
+    MissingAccount,
+    /// Oracle sources disagree beyond the asset's tolerance
+    OracleDisagreement,
+    /// More than one account was supplied for the same oracle source
+    DuplicateAccount,
+}
+
+/// Kind of oracle account an asset settles against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OracleKind {
+    /// Legacy Pyth push price account pinned by address
+    PythLegacy,
+    /// Pyth pull-oracle PriceUpdateV2 account matching a feed id
+    PythPull,
+    /// Switchboard V2 aggregator account pinned by address
+    Switchboard,
+    /// Admin-set MockPrice PDA pinned by address (mock-oracle builds only)
+    #[cfg(feature = "mock-oracle")]
+    Mock,
+}
+
+/// One oracle feed an asset can settle against
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OracleSource {
+    /// Kind of oracle account
+    pub kind: OracleKind,
+    /// Feed account address (PythLegacy and Switchboard)
+    pub account: Pubkey,
+    /// Feed id carried by price updates (PythPull)
+    pub feed_id: [u8; 32],
+}
+
+impl OracleSource {
+    pub const LEN: usize = 1 + // kind (enum)
+        32 + // account
+        32; // feed_id
+
+    /// Whether `account` could hold a price for this source
+    /// Pull updates are matched by owner here and by feed id when decoded
+    pub fn claims(&self, account: &AccountInfo) -> bool {
+        match self.kind {
+            OracleKind::PythLegacy | OracleKind::Switchboard => account.key == &self.account,
+            #[cfg(feature = "mock-oracle")]
+            OracleKind::Mock => account.key == &self.account,
+            OracleKind::PythPull => account.owner == &pyth_pull::PYTH_RECEIVER_PROGRAM_ID,
+        }
+    }
+
+    /// Whether both sources read the same feed (same pull feed id, or same account otherwise)
+    pub fn same_feed(&self, other: &OracleSource) -> bool {
+        self.kind == other.kind
+            && match self.kind {
+                OracleKind::PythPull => self.feed_id == other.feed_id,
+                _ => self.account == other.account,
+            }
+    }
+}
+
+/// Aggregate trading status reported by the oracle
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PriceStatus {
+    Unknown,
+    Trading,
+    Halted,
+    Auction,
+    Ignored,
+}
+
+/// Decode a single oracle account according to the source's kind
+pub fn load_price(
+    account: &AccountInfo,
+    pyth_program: &Pubkey,
+    source: &OracleSource,
+) -> std::result::Result<OraclePrice, OracleError> {
+    match source.kind {
+        OracleKind::PythLegacy => pyth::load_price_account(account, pyth_program),
+        OracleKind::PythPull => pyth_pull::load_price_update(account, &source.feed_id),
+        OracleKind::Switchboard => switchboard::load_aggregator(account),
+        #[cfg(feature = "mock-oracle")]
+        OracleKind::Mock => mock::load_mock_price(account),
+    }
+}
+
+/// Read the source's one candidate account and check it against the asset's guards
+/// Candidates belonging to other sources are skipped; if none belong to this source, MissingAccount is
+/// returned, and a second account for it is DuplicateAccount, so the caller cannot pick among prices
+/// Pull updates must also be published at or just after `reference_ts` (see pyth_pull::check_publish_window)
+pub fn read_price(
+    candidates: &[AccountInfo],
+    pyth_program: &Pubkey,
+    source: &OracleSource,
+    asset_config: &AssetConfig,
+    reference_ts: i64,
+) -> std::result::Result<OraclePrice, OracleError> {
+    let mut found: Option<(&Pubkey, std::result::Result<OraclePrice, OracleError>)> = None;
+
+    for account in candidates.iter().filter(|account| source.claims(account)) {
+        let loaded = load_price(account, pyth_program, source);
+        // An update for another pull feed belongs to a different source
+        if loaded == Err(OracleError::FeedIdMismatch) {
+            continue;
+        }
+        match found {
+            // The same account passed twice still holds one price
+            Some((key, _)) if key == account.key => {}
+            Some(_) => return Err(OracleError::DuplicateAccount),
+            None => found = Some((account.key, loaded)),
+        }
+    }
+
+    let price = found.ok_or(OracleError::MissingAccount)?.1?;
+    if source.kind == OracleKind::PythPull {
+        pyth_pull::check_publish_window(price.publish_time, reference_ts)?;
+    }
+    price.validate(asset_config, reference_ts)
+}
+
+/// A decoded oracle price (price = mantissa * 10^expo)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OraclePrice {
+    /// Aggregate price mantissa
+    pub price: i64,
+    /// Confidence interval around the price (same exponent)
+    pub conf: u64,
+    /// Price exponent
+    pub expo: i32,
+    /// Aggregate trading status
+    pub status: PriceStatus,
+    /// Unix timestamp of the aggregate price
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    /// Enforce the asset's status, staleness and confidence guards
+    /// `reference_ts` is the moment the price should describe (e.g. epoch end); the price may be up to
+    /// max_price_age_secs older than that, or at most MAX_PUBLISH_DELAY_SECS newer
+    pub fn validate(
+        self,
+        asset_config: &AssetConfig,
+        reference_ts: i64,
+    ) -> std::result::Result<Self, OracleError> {
+        if self.status != asset_config.required_status {
+            return Err(OracleError::NotTrading);
+        }
+        if self.price <= 0 {
+            return Err(OracleError::NonPositivePrice);
+        }
+        if self.publish_time > reference_ts.saturating_add(MAX_PUBLISH_DELAY_SECS)
+            || reference_ts.saturating_sub(self.publish_time) > asset_config.max_price_age_secs as i64
+        {
+            return Err(OracleError::StalePrice);
+        }
+        // conf / price > max_conf_bps / 10_000
+        if (self.conf as u128) * 10_000 > (self.price as u128) * asset_config.max_conf_bps as u128 {
+            return Err(OracleError::ConfidenceTooWide);
+        }
+        Ok(self)
+    }
+}
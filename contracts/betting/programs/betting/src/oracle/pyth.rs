@@ -0,0 +1,209 @@
+use anchor_lang::prelude::*;
+use super::{OracleError, OraclePrice, PriceStatus};
+
+/// Magic number at the start of every Pyth account
+pub const PYTH_MAGIC: u32 = 0xa1b2_c3d4;
+/// Supported Pyth account layout version
+pub const PYTH_VERSION: u32 = 2;
+/// Account type discriminator for price accounts
+pub const PYTH_ACCOUNT_TYPE_PRICE: u32 = 3;
+
+// Byte offsets into the Pyth v2 price account layout
+const MAGIC_OFFSET: usize = 0;
+const VERSION_OFFSET: usize = 4;
+const ACCOUNT_TYPE_OFFSET: usize = 8;
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+/// Bytes required to read the header and aggregate price
+const MIN_PRICE_ACCOUNT_LEN: usize = 240;
+
+/// Decode a legacy Pyth price account owned by `pyth_program`
+pub fn load_price_account(
+    account: &AccountInfo,
+    pyth_program: &Pubkey,
+) -> std::result::Result<OraclePrice, OracleError> {
+    if account.owner != pyth_program {
+        return Err(OracleError::WrongOwner);
+    }
+
+    let data = account.try_borrow_data().map_err(|_| OracleError::AccountTooSmall)?;
+    parse_price_account(&data)
+}
+
+/// Decode the raw bytes of a legacy Pyth price account
+pub fn parse_price_account(data: &[u8]) -> std::result::Result<OraclePrice, OracleError> {
+    if data.len() < MIN_PRICE_ACCOUNT_LEN {
+        return Err(OracleError::AccountTooSmall);
+    }
+
+    if read_u32(data, MAGIC_OFFSET) != PYTH_MAGIC {
+        return Err(OracleError::BadMagic);
+    }
+    if read_u32(data, VERSION_OFFSET) != PYTH_VERSION {
+        return Err(OracleError::BadVersion);
+    }
+    if read_u32(data, ACCOUNT_TYPE_OFFSET) != PYTH_ACCOUNT_TYPE_PRICE {
+        return Err(OracleError::BadAccountType);
+    }
+
+    let status = match read_u32(data, AGG_STATUS_OFFSET) {
+        1 => PriceStatus::Trading,
+        2 => PriceStatus::Halted,
+        3 => PriceStatus::Auction,
+        4 => PriceStatus::Ignored,
+        _ => PriceStatus::Unknown,
+    };
+
+    Ok(OraclePrice {
+        price: read_i64(data, AGG_PRICE_OFFSET),
+        conf: read_u64(data, AGG_CONF_OFFSET),
+        expo: read_u32(data, EXPO_OFFSET) as i32,
+        status,
+        publish_time: read_i64(data, TIMESTAMP_OFFSET),
+    })
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+fn read_i64(data: &[u8], offset: usize) -> i64 {
+    i64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::{OracleKind, OracleSource, MAX_PUBLISH_DELAY_SECS};
+    use crate::state::tests::asset_with_sources;
+    use crate::state::AssetConfig;
+
+    const PUBLISH_TIME: i64 = 1_700_000_400;
+
+    /// A v2 price account trading at 60,000.00000000 with a 0.1% confidence interval
+    fn price_account() -> Vec<u8> {
+        let mut data = vec![0u8; MIN_PRICE_ACCOUNT_LEN];
+        data[MAGIC_OFFSET..MAGIC_OFFSET + 4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[VERSION_OFFSET..VERSION_OFFSET + 4].copy_from_slice(&PYTH_VERSION.to_le_bytes());
+        data[ACCOUNT_TYPE_OFFSET..ACCOUNT_TYPE_OFFSET + 4]
+            .copy_from_slice(&PYTH_ACCOUNT_TYPE_PRICE.to_le_bytes());
+        data[EXPO_OFFSET..EXPO_OFFSET + 4].copy_from_slice(&(-8i32).to_le_bytes());
+        data[TIMESTAMP_OFFSET..TIMESTAMP_OFFSET + 8].copy_from_slice(&PUBLISH_TIME.to_le_bytes());
+        set_aggregate(&mut data, 6_000_000_000_000, 6_000_000_000, 1);
+        data
+    }
+
+    fn set_aggregate(data: &mut [u8], price: i64, conf: u64, status: u32) {
+        data[AGG_PRICE_OFFSET..AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[AGG_CONF_OFFSET..AGG_CONF_OFFSET + 8].copy_from_slice(&conf.to_le_bytes());
+        data[AGG_STATUS_OFFSET..AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+    }
+
+    fn asset_config() -> AssetConfig {
+        asset_with_sources(
+            OracleSource {
+                kind: OracleKind::PythLegacy,
+                account: Pubkey::new_unique(),
+                feed_id: [0; 32],
+            },
+            &[],
+        )
+    }
+
+    fn load(data: &mut [u8], owner: &Pubkey, pyth_program: &Pubkey) -> std::result::Result<OraclePrice, OracleError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        load_price_account(&account, pyth_program)
+    }
+
+    #[test]
+    fn decodes_a_trading_price_account() {
+        let pyth_program = Pubkey::new_unique();
+        let price = load(&mut price_account(), &pyth_program, &pyth_program).unwrap();
+        assert_eq!(
+            price,
+            OraclePrice {
+                price: 6_000_000_000_000,
+                conf: 6_000_000_000,
+                expo: -8,
+                status: PriceStatus::Trading,
+                publish_time: PUBLISH_TIME,
+            }
+        );
+        assert_eq!(price.validate(&asset_config(), PUBLISH_TIME), Ok(price));
+    }
+
+    #[test]
+    fn rejects_accounts_of_another_program() {
+        let pyth_program = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        assert_eq!(load(&mut price_account(), &other, &pyth_program), Err(OracleError::WrongOwner));
+    }
+
+    #[test]
+    fn rejects_foreign_or_truncated_layouts() {
+        let mut data = price_account();
+        data[MAGIC_OFFSET] ^= 0xff;
+        assert_eq!(parse_price_account(&data), Err(OracleError::BadMagic));
+
+        let mut data = price_account();
+        data[VERSION_OFFSET..VERSION_OFFSET + 4].copy_from_slice(&1u32.to_le_bytes());
+        assert_eq!(parse_price_account(&data), Err(OracleError::BadVersion));
+
+        let mut data = price_account();
+        data[ACCOUNT_TYPE_OFFSET..ACCOUNT_TYPE_OFFSET + 4].copy_from_slice(&2u32.to_le_bytes());
+        assert_eq!(parse_price_account(&data), Err(OracleError::BadAccountType));
+
+        let data = price_account();
+        assert_eq!(
+            parse_price_account(&data[..MIN_PRICE_ACCOUNT_LEN - 1]),
+            Err(OracleError::AccountTooSmall)
+        );
+    }
+
+    #[test]
+    fn guards_reject_non_trading_stale_and_uncertain_prices() {
+        let asset_config = asset_config();
+
+        let mut data = price_account();
+        set_aggregate(&mut data, 6_000_000_000_000, 6_000_000_000, 2);
+        let halted = parse_price_account(&data).unwrap();
+        assert_eq!(halted.status, PriceStatus::Halted);
+        assert_eq!(halted.validate(&asset_config, PUBLISH_TIME), Err(OracleError::NotTrading));
+
+        let price = parse_price_account(&price_account()).unwrap();
+        let max_age = asset_config.max_price_age_secs as i64;
+        assert_eq!(price.validate(&asset_config, PUBLISH_TIME + max_age), Ok(price));
+        assert_eq!(
+            price.validate(&asset_config, PUBLISH_TIME + max_age + 1),
+            Err(OracleError::StalePrice)
+        );
+
+        // 1.01% confidence against the default 1% limit
+        let mut data = price_account();
+        set_aggregate(&mut data, 6_000_000_000_000, 60_600_000_000, 1);
+        let uncertain = parse_price_account(&data).unwrap();
+        assert_eq!(
+            uncertain.validate(&asset_config, PUBLISH_TIME),
+            Err(OracleError::ConfidenceTooWide)
+        );
+    }
+
+    #[test]
+    fn rejects_prices_published_well_after_the_reference_time() {
+        let asset_config = asset_config();
+        let price = parse_price_account(&price_account()).unwrap();
+        let end_ts = PUBLISH_TIME - MAX_PUBLISH_DELAY_SECS;
+        assert_eq!(price.validate(&asset_config, end_ts), Ok(price));
+        // Published after end_ts by more than slot timing, though well within max_price_age_secs
+        assert_eq!(price.validate(&asset_config, end_ts - 1), Err(OracleError::StalePrice));
+    }
+}
@@ -0,0 +1,207 @@
+use anchor_lang::prelude::*;
+use super::{OracleError, OraclePrice, PriceStatus, MAX_PUBLISH_DELAY_SECS};
+
+/// Pyth Solana receiver program that owns every PriceUpdateV2 account
+pub const PYTH_RECEIVER_PROGRAM_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+/// Anchor discriminator of the PriceUpdateV2 account
+pub const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
+// VerificationLevel tags (Borsh enum discriminants)
+const VERIFICATION_PARTIAL: u8 = 0;
+const VERIFICATION_FULL: u8 = 1;
+
+// Layout: discriminator (8) + write_authority (32) + verification_level (1 or 2)
+const VERIFICATION_OFFSET: usize = 8 + 32;
+// PriceFeedMessage: feed_id (32) + price (8) + conf (8) + exponent (4) + publish_time (8)
+const PRICE_MESSAGE_LEN: usize = 32 + 8 + 8 + 4 + 8;
+
+/// Decode a PriceUpdateV2 account and check it carries `feed_id` with full verification
+pub fn load_price_update(
+    account: &AccountInfo,
+    feed_id: &[u8; 32],
+) -> std::result::Result<OraclePrice, OracleError> {
+    if account.owner != &PYTH_RECEIVER_PROGRAM_ID {
+        return Err(OracleError::WrongOwner);
+    }
+
+    let data = account.try_borrow_data().map_err(|_| OracleError::AccountTooSmall)?;
+    parse_price_update(&data, feed_id)
+}
+
+/// Decode the raw bytes of a PriceUpdateV2 account
+pub fn parse_price_update(
+    data: &[u8],
+    feed_id: &[u8; 32],
+) -> std::result::Result<OraclePrice, OracleError> {
+    if data.len() < VERIFICATION_OFFSET + 1 {
+        return Err(OracleError::AccountTooSmall);
+    }
+    if data[..8] != PRICE_UPDATE_V2_DISCRIMINATOR {
+        return Err(OracleError::BadAccountType);
+    }
+
+    // Partial verification carries an extra num_signatures byte and is never accepted
+    let message_offset = match data[VERIFICATION_OFFSET] {
+        VERIFICATION_FULL => VERIFICATION_OFFSET + 1,
+        VERIFICATION_PARTIAL => return Err(OracleError::InsufficientVerification),
+        _ => return Err(OracleError::BadAccountType),
+    };
+
+    if data.len() < message_offset + PRICE_MESSAGE_LEN {
+        return Err(OracleError::AccountTooSmall);
+    }
+    let message = &data[message_offset..message_offset + PRICE_MESSAGE_LEN];
+
+    if &message[..32] != feed_id {
+        return Err(OracleError::FeedIdMismatch);
+    }
+
+    Ok(OraclePrice {
+        price: i64::from_le_bytes(message[32..40].try_into().unwrap()),
+        conf: u64::from_le_bytes(message[40..48].try_into().unwrap()),
+        expo: i32::from_le_bytes(message[48..52].try_into().unwrap()),
+        // Pull updates are only published while the feed is trading
+        status: PriceStatus::Trading,
+        publish_time: i64::from_le_bytes(message[52..60].try_into().unwrap()),
+    })
+}
+
+/// Require an update published within MAX_PUBLISH_DELAY_SECS at or after `reference_ts`
+/// Anyone can post updates, so unlike push feeds an older one is never accepted: the first update at
+/// or after the reference time must be used, not one chosen later with a more favourable price
+pub fn check_publish_window(
+    publish_time: i64,
+    reference_ts: i64,
+) -> std::result::Result<(), OracleError> {
+    if publish_time < reference_ts || publish_time - reference_ts > MAX_PUBLISH_DELAY_SECS {
+        return Err(OracleError::StalePrice);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::{OracleKind, OracleSource};
+    use crate::state::tests::asset_with_sources;
+
+    const FEED_ID: [u8; 32] = [7; 32];
+    const PUBLISH_TIME: i64 = 1_700_000_400;
+
+    /// A fully verified PriceUpdateV2 for FEED_ID at 60,000.00000000 with a 0.1% confidence interval
+    fn price_update(price: i64, conf: u64) -> Vec<u8> {
+        let mut data = PRICE_UPDATE_V2_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[9; 32]); // write_authority
+        data.push(VERIFICATION_FULL);
+        data.extend_from_slice(&FEED_ID);
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&conf.to_le_bytes());
+        data.extend_from_slice(&(-8i32).to_le_bytes());
+        data.extend_from_slice(&PUBLISH_TIME.to_le_bytes());
+        data.extend_from_slice(&[0; 8]); // prev_publish_time
+        data
+    }
+
+    fn load(data: &mut [u8], owner: &Pubkey) -> std::result::Result<OraclePrice, OracleError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        load_price_update(&account, &FEED_ID)
+    }
+
+    #[test]
+    fn decodes_a_verified_price_update() {
+        let price = load(&mut price_update(6_000_000_000_000, 6_000_000_000), &PYTH_RECEIVER_PROGRAM_ID)
+            .unwrap();
+        assert_eq!(
+            price,
+            OraclePrice {
+                price: 6_000_000_000_000,
+                conf: 6_000_000_000,
+                expo: -8,
+                status: PriceStatus::Trading,
+                publish_time: PUBLISH_TIME,
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_accounts_of_another_program() {
+        let other = Pubkey::new_unique();
+        assert_eq!(
+            load(&mut price_update(6_000_000_000_000, 0), &other),
+            Err(OracleError::WrongOwner)
+        );
+    }
+
+    #[test]
+    fn rejects_foreign_unverified_or_truncated_updates() {
+        let mut data = price_update(6_000_000_000_000, 0);
+        data[0] ^= 0xff;
+        assert_eq!(parse_price_update(&data, &FEED_ID), Err(OracleError::BadAccountType));
+
+        let mut data = price_update(6_000_000_000_000, 0);
+        data[VERIFICATION_OFFSET] = VERIFICATION_PARTIAL;
+        assert_eq!(parse_price_update(&data, &FEED_ID), Err(OracleError::InsufficientVerification));
+
+        let mut data = price_update(6_000_000_000_000, 0);
+        data[VERIFICATION_OFFSET] = 2;
+        assert_eq!(parse_price_update(&data, &FEED_ID), Err(OracleError::BadAccountType));
+
+        let data = price_update(6_000_000_000_000, 0);
+        assert_eq!(parse_price_update(&data, &[8; 32]), Err(OracleError::FeedIdMismatch));
+        assert_eq!(
+            parse_price_update(&data[..VERIFICATION_OFFSET + PRICE_MESSAGE_LEN], &FEED_ID),
+            Err(OracleError::AccountTooSmall)
+        );
+        assert_eq!(
+            parse_price_update(&data[..VERIFICATION_OFFSET], &FEED_ID),
+            Err(OracleError::AccountTooSmall)
+        );
+    }
+
+    #[test]
+    fn guards_reject_non_trading_stale_and_uncertain_prices() {
+        let mut asset_config = asset_with_sources(
+            OracleSource {
+                kind: OracleKind::PythPull,
+                account: Pubkey::default(),
+                feed_id: FEED_ID,
+            },
+            &[],
+        );
+
+        let price = parse_price_update(&price_update(6_000_000_000_000, 6_000_000_000), &FEED_ID).unwrap();
+        assert_eq!(price.validate(&asset_config, PUBLISH_TIME), Ok(price));
+
+        let max_age = asset_config.max_price_age_secs as i64;
+        assert_eq!(
+            price.validate(&asset_config, PUBLISH_TIME + max_age + 1),
+            Err(OracleError::StalePrice)
+        );
+
+        // 1.01% confidence against the default 1% limit
+        let uncertain = parse_price_update(&price_update(6_000_000_000_000, 60_600_000_000), &FEED_ID).unwrap();
+        assert_eq!(
+            uncertain.validate(&asset_config, PUBLISH_TIME),
+            Err(OracleError::ConfidenceTooWide)
+        );
+
+        // Updates are only published while trading, so any other required status rejects them
+        asset_config.required_status = PriceStatus::Halted;
+        assert_eq!(price.validate(&asset_config, PUBLISH_TIME), Err(OracleError::NotTrading));
+    }
+
+    #[test]
+    fn publish_window_is_one_sided() {
+        let end_ts = 1_700_000_400;
+        assert_eq!(check_publish_window(end_ts, end_ts), Ok(()));
+        assert_eq!(check_publish_window(end_ts + MAX_PUBLISH_DELAY_SECS, end_ts), Ok(()));
+        // Updates from before the reference time or chosen later are rejected
+        assert_eq!(check_publish_window(end_ts - 1, end_ts), Err(OracleError::StalePrice));
+        assert_eq!(
+            check_publish_window(end_ts + MAX_PUBLISH_DELAY_SECS + 1, end_ts),
+            Err(OracleError::StalePrice)
+        );
+    }
+}
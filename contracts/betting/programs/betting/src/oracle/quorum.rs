@@ -0,0 +1,175 @@
+use anchor_lang::prelude::*;
+use super::{read_price, OracleError, OraclePrice, OracleSource, PriceStatus};
+use crate::state::{normalize_price, AssetConfig};
+
+/// How an asset's oracle sources are combined into one price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QuorumRule {
+    /// Use the median; a majority of sources must agree with it within tolerance
+    Median,
+    /// Every source must be usable and agree with the median within tolerance
+    AllAgree,
+}
+
+/// A source whose price could not be used or strayed from the median
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Outlier {
+    /// Position in AssetConfig::oracle_sources (0 = primary)
+    pub index: u8,
+    pub source: OracleSource,
+    /// Distance from the median in basis points (0 when the source had no usable price)
+    pub deviation_bps: u64,
+    /// Why the source was rejected
+    pub reason: OracleError,
+}
+
+/// Combined price plus any sources that were outvoted
+pub struct QuorumPrice {
+    pub price: OraclePrice,
+    pub outliers: Vec<Outlier>,
+}
+
+/// Why the sources could not agree on a price
+pub struct QuorumFailure {
+    pub reason: OracleError,
+    /// The source most responsible for the failure, when more than one source is configured
+    pub outlier: Option<Outlier>,
+}
+
+/// Distance of `value` from `median` in basis points of the median
+/// Saturates at u64::MAX when prices at wildly different exponents overflow, so such a source is
+/// outvoted (or the epoch invalidated) instead of the settlement reverting
+pub fn deviation_bps(value: i128, median: i128) -> u64 {
+    value
+        .abs_diff(median)
+        .checked_mul(10_000)
+        .map(|scaled| scaled / median.unsigned_abs().max(1))
+        .and_then(|bps| u64::try_from(bps).ok())
+        .unwrap_or(u64::MAX)
+}
+
+/// Read every configured source for the asset and combine them under its quorum rule
+pub fn read_quorum_price(
+    candidates: &[AccountInfo],
+    pyth_program: &Pubkey,
+    asset_config: &AssetConfig,
+    reference_ts: i64,
+) -> std::result::Result<QuorumPrice, QuorumFailure> {
+    let sources = asset_config.oracle_sources();
+    let multi = sources.len() > 1;
+    let fail = |reason: OracleError, outlier: Option<Outlier>| QuorumFailure {
+        reason,
+        outlier: outlier.filter(|_| multi),
+    };
+
+    let mut valid: Vec<(u8, OracleSource, OraclePrice)> = Vec::with_capacity(sources.len());
+    let mut rejected: Vec<Outlier> = Vec::new();
+
+    for (index, source) in sources.iter().enumerate() {
+        match read_price(candidates, pyth_program, source, asset_config, reference_ts) {
+            Ok(price) => valid.push((index as u8, *source, price)),
+            // A missing or duplicated account is the caller's fault, never a reason to settle
+            Err(reason @ (OracleError::MissingAccount | OracleError::DuplicateAccount)) => {
+                return Err(fail(reason, None))
+            }
+            Err(reason) => rejected.push(Outlier {
+                index: index as u8,
+                source: *source,
+                deviation_bps: 0,
+                reason,
+            }),
+        }
+    }
+
+    if valid.is_empty() || (asset_config.quorum_rule == QuorumRule::AllAgree && !rejected.is_empty()) {
+        let first = rejected[0];
+        return Err(fail(first.reason, Some(first)));
+    }
+
+    // Compare every price at the finest exponent
+    let expo = valid.iter().map(|(_, _, p)| p.expo).min().unwrap();
+    let mut values = Vec::with_capacity(valid.len());
+    for (_, _, p) in &valid {
+        values.push(
+            normalize_price(p.price, p.expo, expo).map_err(|_| fail(OracleError::PriceOutOfRange, None))?,
+        );
+    }
+
+    let mut sorted = values.clone();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 1 {
+        sorted[mid]
+    } else {
+        sorted[mid - 1]
+            .checked_add(sorted[mid])
+            .ok_or_else(|| fail(OracleError::PriceOutOfRange, None))?
+            / 2
+    };
+
+    let mut agreeing = Vec::with_capacity(valid.len());
+    for ((index, source, price), value) in valid.iter().zip(&values) {
+        let deviation_bps = deviation_bps(*value, median);
+        if deviation_bps > asset_config.quorum_tolerance_bps as u64 {
+            rejected.push(Outlier {
+                index: *index,
+                source: *source,
+                deviation_bps,
+                reason: OracleError::OracleDisagreement,
+            });
+        } else {
+            agreeing.push(*price);
+        }
+    }
+
+    let quorum_met = match asset_config.quorum_rule {
+        QuorumRule::Median => agreeing.len() * 2 > sources.len(),
+        QuorumRule::AllAgree => agreeing.len() == sources.len(),
+    };
+    if !quorum_met {
+        // Blame the furthest price outlier, or the first unusable source
+        let outlier = rejected
+            .iter()
+            .rev()
+            .copied()
+            .max_by_key(|o| o.deviation_bps)
+            .unwrap();
+        return Err(fail(outlier.reason, Some(outlier)));
+    }
+
+    let price = i64::try_from(median).map_err(|_| fail(OracleError::PriceOutOfRange, None))?;
+    Ok(QuorumPrice {
+        price: OraclePrice {
+            price,
+            conf: agreeing
+                .iter()
+                .map(|p| p.conf.saturating_mul(10u64.saturating_pow((p.expo - expo) as u32)))
+                .max()
+                .unwrap_or(0),
+            expo,
+            status: PriceStatus::Trading,
+            publish_time: agreeing[0].publish_time,
+        },
+        outliers: rejected,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deviation_is_measured_against_the_median() {
+        assert_eq!(deviation_bps(6_000_000_000_000, 6_000_000_000_000), 0);
+        assert_eq!(deviation_bps(6_030_000_000_000, 6_000_000_000_000), 50);
+        assert_eq!(deviation_bps(5_970_000_000_000, 6_000_000_000_000), 50);
+    }
+
+    #[test]
+    fn overflowing_deviation_saturates_instead_of_panicking() {
+        // A price rescaled to a much finer exponent lands near the i128 limits
+        assert_eq!(deviation_bps(i128::MAX, 6_000_000_000_000), u64::MAX);
+        assert_eq!(deviation_bps(i128::MIN, i128::MAX), u64::MAX);
+        assert_eq!(deviation_bps(i128::MAX / 100, 1), u64::MAX);
+    }
+}
@@ -0,0 +1,196 @@
+use anchor_lang::prelude::*;
+use super::{OracleError, OraclePrice, PriceStatus};
+
+/// Switchboard V2 program that owns every aggregator account
+pub const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+/// Anchor discriminator of the AggregatorAccountData account
+pub const AGGREGATOR_DISCRIMINATOR: [u8; 8] = [217, 230, 65, 101, 201, 162, 27, 125];
+
+// Byte offsets into the packed AggregatorAccountData layout (latest_confirmed_round)
+const ROUND_OFFSET: usize = 341;
+const NUM_SUCCESS_OFFSET: usize = ROUND_OFFSET;
+const ROUND_OPEN_TIMESTAMP_OFFSET: usize = ROUND_OFFSET + 17;
+const RESULT_OFFSET: usize = ROUND_OFFSET + 25;
+const STD_DEVIATION_OFFSET: usize = RESULT_OFFSET + 20;
+/// Bytes required to read the latest confirmed round result
+const MIN_AGGREGATOR_LEN: usize = STD_DEVIATION_OFFSET + 20;
+
+/// Decode a Switchboard V2 aggregator's latest confirmed round
+pub fn load_aggregator(account: &AccountInfo) -> std::result::Result<OraclePrice, OracleError> {
+    if account.owner != &SWITCHBOARD_PROGRAM_ID {
+        return Err(OracleError::WrongOwner);
+    }
+
+    let data = account.try_borrow_data().map_err(|_| OracleError::AccountTooSmall)?;
+    parse_aggregator(&data)
+}
+
+/// Decode the raw bytes of a Switchboard V2 aggregator account
+pub fn parse_aggregator(data: &[u8]) -> std::result::Result<OraclePrice, OracleError> {
+    if data.len() < MIN_AGGREGATOR_LEN {
+        return Err(OracleError::AccountTooSmall);
+    }
+    if data[..8] != AGGREGATOR_DISCRIMINATOR {
+        return Err(OracleError::BadAccountType);
+    }
+
+    // A round without successful oracle responses carries no price
+    let num_success = u32::from_le_bytes(data[NUM_SUCCESS_OFFSET..NUM_SUCCESS_OFFSET + 4].try_into().unwrap());
+    let status = if num_success > 0 { PriceStatus::Trading } else { PriceStatus::Unknown };
+
+    // SwitchboardDecimal: value = mantissa (i128) * 10^-scale (u32)
+    let (mantissa, scale) = read_decimal(data, RESULT_OFFSET);
+    let (std_mantissa, std_scale) = read_decimal(data, STD_DEVIATION_OFFSET);
+
+    let price = i64::try_from(mantissa).map_err(|_| OracleError::PriceOutOfRange)?;
+    let expo = -i32::try_from(scale).map_err(|_| OracleError::PriceOutOfRange)?;
+
+    // Express the standard deviation at the price's scale
+    let conf = if std_scale >= scale {
+        std_mantissa.unsigned_abs() / 10u128.saturating_pow(std_scale - scale)
+    } else {
+        std_mantissa.unsigned_abs().saturating_mul(10u128.saturating_pow(scale - std_scale))
+    };
+
+    Ok(OraclePrice {
+        price,
+        conf: u64::try_from(conf).unwrap_or(u64::MAX),
+        expo,
+        status,
+        publish_time: i64::from_le_bytes(
+            data[ROUND_OPEN_TIMESTAMP_OFFSET..ROUND_OPEN_TIMESTAMP_OFFSET + 8].try_into().unwrap(),
+        ),
+    })
+}
+
+fn read_decimal(data: &[u8], offset: usize) -> (i128, u32) {
+    let mantissa = i128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+    let scale = u32::from_le_bytes(data[offset + 16..offset + 20].try_into().unwrap());
+    (mantissa, scale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::{OracleKind, OracleSource, MAX_PUBLISH_DELAY_SECS};
+    use crate::state::tests::asset_with_sources;
+    use crate::state::AssetConfig;
+
+    const ROUND_OPEN_TIMESTAMP: i64 = 1_700_000_400;
+
+    /// An aggregator whose latest round is 60,000.00000000 with a 0.1% standard deviation
+    fn aggregator() -> Vec<u8> {
+        let mut data = vec![0u8; MIN_AGGREGATOR_LEN];
+        data[..8].copy_from_slice(&AGGREGATOR_DISCRIMINATOR);
+        data[ROUND_OPEN_TIMESTAMP_OFFSET..ROUND_OPEN_TIMESTAMP_OFFSET + 8]
+            .copy_from_slice(&ROUND_OPEN_TIMESTAMP.to_le_bytes());
+        set_round(&mut data, 3, (6_000_000_000_000, 8), (6_000_000_000, 8));
+        data
+    }
+
+    fn set_round(data: &mut [u8], num_success: u32, result: (i128, u32), std_deviation: (i128, u32)) {
+        data[NUM_SUCCESS_OFFSET..NUM_SUCCESS_OFFSET + 4].copy_from_slice(&num_success.to_le_bytes());
+        for (offset, (mantissa, scale)) in [(RESULT_OFFSET, result), (STD_DEVIATION_OFFSET, std_deviation)] {
+            data[offset..offset + 16].copy_from_slice(&mantissa.to_le_bytes());
+            data[offset + 16..offset + 20].copy_from_slice(&scale.to_le_bytes());
+        }
+    }
+
+    fn asset_config() -> AssetConfig {
+        asset_with_sources(
+            OracleSource {
+                kind: OracleKind::Switchboard,
+                account: Pubkey::new_unique(),
+                feed_id: [0; 32],
+            },
+            &[],
+        )
+    }
+
+    fn load(data: &mut [u8], owner: &Pubkey) -> std::result::Result<OraclePrice, OracleError> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let account = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        load_aggregator(&account)
+    }
+
+    #[test]
+    fn decodes_the_latest_confirmed_round() {
+        let price = load(&mut aggregator(), &SWITCHBOARD_PROGRAM_ID).unwrap();
+        assert_eq!(
+            price,
+            OraclePrice {
+                price: 6_000_000_000_000,
+                conf: 6_000_000_000,
+                expo: -8,
+                status: PriceStatus::Trading,
+                publish_time: ROUND_OPEN_TIMESTAMP,
+            }
+        );
+        assert_eq!(price.validate(&asset_config(), ROUND_OPEN_TIMESTAMP), Ok(price));
+
+        // A standard deviation at another scale is expressed at the price's scale
+        let mut data = aggregator();
+        set_round(&mut data, 3, (6_000_000_000_000, 8), (6_000, 2));
+        assert_eq!(parse_aggregator(&data).unwrap().conf, 6_000_000_000);
+    }
+
+    #[test]
+    fn rejects_accounts_of_another_program() {
+        let other = Pubkey::new_unique();
+        assert_eq!(load(&mut aggregator(), &other), Err(OracleError::WrongOwner));
+    }
+
+    #[test]
+    fn rejects_foreign_truncated_or_oversized_rounds() {
+        let mut data = aggregator();
+        data[0] ^= 0xff;
+        assert_eq!(parse_aggregator(&data), Err(OracleError::BadAccountType));
+
+        let data = aggregator();
+        assert_eq!(parse_aggregator(&data[..MIN_AGGREGATOR_LEN - 1]), Err(OracleError::AccountTooSmall));
+
+        let mut data = aggregator();
+        set_round(&mut data, 3, (i64::MAX as i128 + 1, 8), (0, 8));
+        assert_eq!(parse_aggregator(&data), Err(OracleError::PriceOutOfRange));
+    }
+
+    #[test]
+    fn guards_reject_non_trading_stale_and_uncertain_prices() {
+        let asset_config = asset_config();
+
+        // A round without successful responses has no trading price
+        let mut data = aggregator();
+        set_round(&mut data, 0, (6_000_000_000_000, 8), (6_000_000_000, 8));
+        let empty = parse_aggregator(&data).unwrap();
+        assert_eq!(empty.status, PriceStatus::Unknown);
+        assert_eq!(empty.validate(&asset_config, ROUND_OPEN_TIMESTAMP), Err(OracleError::NotTrading));
+
+        let price = parse_aggregator(&aggregator()).unwrap();
+        let max_age = asset_config.max_price_age_secs as i64;
+        assert_eq!(price.validate(&asset_config, ROUND_OPEN_TIMESTAMP + max_age), Ok(price));
+        assert_eq!(
+            price.validate(&asset_config, ROUND_OPEN_TIMESTAMP + max_age + 1),
+            Err(OracleError::StalePrice)
+        );
+
+        // 1.01% standard deviation against the default 1% limit
+        let mut data = aggregator();
+        set_round(&mut data, 3, (6_000_000_000_000, 8), (60_600_000_000, 8));
+        let uncertain = parse_aggregator(&data).unwrap();
+        assert_eq!(
+            uncertain.validate(&asset_config, ROUND_OPEN_TIMESTAMP),
+            Err(OracleError::ConfidenceTooWide)
+        );
+    }
+
+    #[test]
+    fn rejects_rounds_opened_well_after_the_reference_time() {
+        let asset_config = asset_config();
+        let price = parse_aggregator(&aggregator()).unwrap();
+        let end_ts = ROUND_OPEN_TIMESTAMP - MAX_PUBLISH_DELAY_SECS;
+        assert_eq!(price.validate(&asset_config, end_ts), Ok(price));
+        // A round opened after end_ts by more than slot timing, though well within max_price_age_secs
+        assert_eq!(price.validate(&asset_config, end_ts - 1), Err(OracleError::StalePrice));
+    }
+}
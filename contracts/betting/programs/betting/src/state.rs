@@ -1,37 +1,88 @@
 use anchor_lang::prelude::*;
+use anchor_lang::error::ErrorCode;
+use anchor_lang::Discriminator;
+use crate::oracle::{OracleKind, OracleSource, PriceStatus};
+use crate::oracle::quorum::QuorumRule;
 
 /// Global configuration for the betting protocol
 /// PDA: ["config"]
 #[account]
 pub struct GlobalConfig {
-    /// Admin authority who can pause and update settings
+    /// Admin authority with full control over settings and roles
     pub admin: Pubkey,
     /// Treasury wallet that receives protocol fees
     pub treasury: Pubkey,
+    /// Pyth oracle program that must own every price account
+    pub pyth_program: Pubkey,
     /// Fee in basis points (100 = 1%)
     pub fee_bps: u16,
     /// Reward in lamports for settling an epoch
     pub settle_tip_lamports: u64,
-    /// Seconds before epoch end when betting is disabled
+    /// Default cutoff for new markets, in seconds before epoch end
     pub cutoff_secs: u32,
-    /// Duration of each epoch in seconds (300 = 5 minutes)
+    /// Default epoch length for new markets, in seconds (300 = 5 minutes)
     pub epoch_length_secs: u32,
     /// Emergency pause flag (pauses place_bet only)
     pub paused: bool,
     /// Bump seed for PDA derivation
     pub bump: u8,
+    /// Proposed new admin awaiting accept_admin (default = none)
+    pub pending_admin: Pubkey,
+    /// Can pause the protocol
+    pub pauser: Pubkey,
+    /// Can change the fee and settle tip
+    pub fee_manager: Pubkey,
+    /// Can register and update asset feeds
+    pub asset_manager: Pubkey,
+    /// Delay before a queued sensitive change can execute (0 = changes apply directly)
+    pub timelock_secs: u32,
+    /// ID assigned to the next queued PendingChange
+    pub next_change_id: u64,
+    /// Share of each epoch fee paid to the settler, in basis points of the fee
+    pub keeper_fee_bps: u16,
+    /// How many grid slots ahead create_epoch_at may pre-create epochs
+    pub max_future_epochs: u16,
+    /// Fee kept from a bet cancelled before cutoff, in basis points of the stake
+    pub cancel_fee_bps: u16,
+    /// Global fee in force when migrate_config upgraded a pre-market config (0 on new deployments)
+    /// Settled pre-market epochs pay out at this fee, whatever fee_bps has become since
+    pub legacy_fee_bps: u16,
 }
 
 impl GlobalConfig {
+    /// Hard cap on the protocol fee (10%)
+    pub const MAX_FEE_BPS: u16 = 1_000;
+
     pub const LEN: usize = 8 + // discriminator
         32 + // admin
         32 + // treasury
+        32 + // pyth_program
         2 + // fee_bps
         8 + // settle_tip_lamports
         4 + // cutoff_secs
         4 + // epoch_length_secs
         1 + // paused
-        1; // bump
+        1 + // bump
+        32 + // pending_admin
+        32 + // pauser
+        32 + // fee_manager
+        32 + // asset_manager
+        4 + // timelock_secs
+        8 + // next_change_id
+        2 + // keeper_fee_bps
+        2 + // max_future_epochs
+        2 + // cancel_fee_bps
+        2; // legacy_fee_bps
+
+    /// Settler's share of an epoch fee; the rest goes to the treasury
+    pub fn keeper_fee(&self, fee_amount: u64) -> u64 {
+        ((fee_amount as u128) * self.keeper_fee_bps as u128 / 10_000u128) as u64
+    }
+
+    /// Part of a cancelled stake that goes to the treasury instead of back to the user
+    pub fn cancel_fee(&self, stake: u64) -> u64 {
+        ((stake as u128) * self.cancel_fee_bps as u128 / 10_000u128) as u64
+    }
 }
 
 /// Configuration for a specific asset (e.g., BTC/USD, ETH/USD)
@@ -40,25 +91,219 @@ impl GlobalConfig {
 pub struct AssetConfig {
     /// Symbol identifier (e.g., "BTCUSD")
     pub asset_symbol: String,
-    /// Pyth price feed account for this asset
+    /// Which kind of oracle account settles this asset
+    pub oracle_kind: OracleKind,
+    /// Pyth price feed account for this asset (legacy push oracle)
     pub pyth_price_account: Pubkey,
-    /// USDC mint address
+    /// Pyth feed id that PriceUpdateV2 accounts must carry (pull oracle)
+    pub feed_id: [u8; 32],
+    /// USDC mint address (fixed once the vault exists)
     pub usdc_mint: Pubkey,
-    /// Current active epoch ID
-    pub active_epoch_id: u64,
+    /// USDC vault created by init_vault (default until then); every stake moves through this account
+    pub vault: Pubkey,
+    /// What happens to the pool when nobody bet on the winning side
+    pub one_sided_policy: OneSidedPolicy,
+    /// Maximum age of an oracle price relative to the time it should describe
+    pub max_price_age_secs: u32,
+    /// Maximum confidence interval relative to price, in basis points
+    pub max_conf_bps: u16,
+    /// Oracle status a price must report to be accepted
+    pub required_status: PriceStatus,
+    /// Additional oracle sources checked against the primary feed
+    pub secondary_oracles: [OracleSource; AssetConfig::MAX_SECONDARY_ORACLES],
+    /// Number of entries in secondary_oracles that are in use
+    pub secondary_oracle_count: u8,
+    /// How the primary and secondary prices are combined
+    pub quorum_rule: QuorumRule,
+    /// Maximum distance from the median price, in basis points
+    pub quorum_tolerance_bps: u16,
+    /// Shift of the asset's market grids past the unix-epoch boundary (wraps at the epoch length)
+    /// Folded into each market's grid_origin_ts at creation, so it is fixed once a market exists
+    pub grid_offset_secs: u32,
+    /// Smallest amount a single place_bet may add (0 = no minimum)
+    pub min_bet: u64,
+    /// Largest amount a single place_bet may add (0 = no maximum)
+    pub max_bet: u64,
+    /// Cap on one user's combined Up and Down stake in an epoch (0 = no cap)
+    pub max_user_stake_per_epoch: u64,
+    /// Cap on an epoch's combined Up and Down pool (0 = no cap)
+    pub max_epoch_pool: u64,
+    /// Number of markets created for this asset (their grids already include grid_offset_secs)
+    pub market_count: u32,
     /// Bump seed for PDA derivation
     pub bump: u8,
 }
 
 impl AssetConfig {
     pub const MAX_SYMBOL_LEN: usize = 16;
+    pub const DEFAULT_MAX_PRICE_AGE_SECS: u32 = 60;
+    pub const DEFAULT_MAX_CONF_BPS: u16 = 100;
+    pub const DEFAULT_QUORUM_TOLERANCE_BPS: u16 = 50;
+    pub const MAX_SECONDARY_ORACLES: usize = 2;
+    /// Decimals every settlement mint must use
+    pub const USDC_DECIMALS: u8 = 6;
 
     pub const LEN: usize = 8 + // discriminator
         4 + Self::MAX_SYMBOL_LEN + // asset_symbol (String with length prefix)
+        1 + // oracle_kind (enum)
         32 + // pyth_price_account
+        32 + // feed_id
         32 + // usdc_mint
+        32 + // vault
+        1 + // one_sided_policy (enum)
+        4 + // max_price_age_secs
+        2 + // max_conf_bps
+        1 + // required_status (enum)
+        OracleSource::LEN * Self::MAX_SECONDARY_ORACLES + // secondary_oracles
+        1 + // secondary_oracle_count
+        1 + // quorum_rule (enum)
+        2 + // quorum_tolerance_bps
+        4 + // grid_offset_secs
+        8 + // min_bet
+        8 + // max_bet
+        8 + // max_user_stake_per_epoch
+        8 + // max_epoch_pool
+        4 + // market_count
+        1; // bump
+
+    /// The primary oracle feed configured through set_asset_feed/update_asset
+    pub fn primary_oracle(&self) -> OracleSource {
+        OracleSource {
+            kind: self.oracle_kind,
+            account: self.pyth_price_account,
+            feed_id: self.feed_id,
+        }
+    }
+
+    /// All oracle sources, primary first
+    pub fn oracle_sources(&self) -> Vec<OracleSource> {
+        let mut sources = vec![self.primary_oracle()];
+        sources.extend_from_slice(&self.secondary_oracles[..self.secondary_oracle_count as usize]);
+        sources
+    }
+
+    /// Whether two oracle sources read the same feed, which would let one feed outvote the others
+    pub fn has_duplicate_oracle_sources(&self) -> bool {
+        let sources = self.oracle_sources();
+        sources
+            .iter()
+            .enumerate()
+            .any(|(i, source)| sources[i + 1..].iter().any(|other| source.same_feed(other)))
+    }
+}
+
+/// A betting market for one asset at one timeframe (e.g. BTCUSD 1m, 5m and 1h run side by side)
+/// PDA: ["market", asset_symbol, timeframe]
+#[account]
+pub struct Market {
+    /// Asset this market trades
+    pub asset_symbol: String,
+    /// Timeframe label (e.g. "5m")
+    pub timeframe: String,
+    /// Duration of each epoch in seconds
+    pub epoch_length_secs: u32,
+    /// Seconds before epoch end when betting is disabled
+    pub cutoff_secs: u32,
+    /// Protocol fee override in basis points (None = GlobalConfig.fee_bps)
+    pub fee_bps: Option<u16>,
+    /// Timestamp from which epoch_length_secs applies
+    pub grid_origin_ts: i64,
+    /// Epoch ID of the slot starting at grid_origin_ts
+    pub grid_origin_epoch_id: u64,
+    /// Epoch length for slots before grid_origin_ts
+    pub prev_epoch_length_secs: u32,
+    /// Current active epoch ID (0 until the first epoch starts; slot 0 lies in 1970 and is never created)
+    pub active_epoch_id: u64,
+    /// Highest epoch ID laid out so far, pre-created epochs included
+    pub last_epoch_id: u64,
+    /// Orphaned USDC waiting to be added to the next epoch's pool
+    pub pending_rollover: u64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl Market {
+    pub const MAX_TIMEFRAME_LEN: usize = 8;
+
+    pub const LEN: usize = 8 + // discriminator
+        4 + AssetConfig::MAX_SYMBOL_LEN + // asset_symbol
+        4 + Self::MAX_TIMEFRAME_LEN + // timeframe
+        4 + // epoch_length_secs
+        4 + // cutoff_secs
+        1 + 2 + // fee_bps (Option)
+        8 + // grid_origin_ts
+        8 + // grid_origin_epoch_id
+        4 + // prev_epoch_length_secs
         8 + // active_epoch_id
+        8 + // last_epoch_id
+        8 + // pending_rollover
         1; // bump
+
+    /// Fee charged on this market's epochs
+    pub fn effective_fee_bps(&self, config: &GlobalConfig) -> u16 {
+        self.fee_bps.unwrap_or(config.fee_bps)
+    }
+
+    /// Whether an epoch earlier than `epoch_id` is still recorded as the market's active epoch
+    pub fn has_active_epoch_before(&self, epoch_id: u64) -> bool {
+        self.active_epoch_id != 0 && self.active_epoch_id < epoch_id
+    }
+
+    /// Epoch ID of the grid slot containing `ts`
+    /// The asset's grid offset is already folded into grid_origin_ts
+    pub fn epoch_id_at(&self, ts: i64) -> u64 {
+        let offset = ts - self.grid_origin_ts;
+        if offset >= 0 {
+            self.grid_origin_epoch_id + ts_to_epoch_id(offset, self.epoch_length_secs, 0)
+        } else {
+            // Slots before the origin keep the previous length
+            let slots_back = (-offset - 1) / self.prev_epoch_length_secs as i64 + 1;
+            self.grid_origin_epoch_id.saturating_sub(slots_back as u64)
+        }
+    }
+
+    /// Length of the grid slot with the given epoch ID
+    pub fn epoch_length_for(&self, epoch_id: u64) -> u32 {
+        if epoch_id >= self.grid_origin_epoch_id {
+            self.epoch_length_secs
+        } else {
+            self.prev_epoch_length_secs
+        }
+    }
+
+    /// Start timestamp of the grid slot with the given epoch ID
+    pub fn epoch_start_ts(&self, epoch_id: u64) -> i64 {
+        if epoch_id >= self.grid_origin_epoch_id {
+            self.grid_origin_ts
+                + epoch_id_to_start_ts(epoch_id - self.grid_origin_epoch_id, self.epoch_length_secs, 0)
+        } else {
+            self.grid_origin_ts
+                - epoch_id_to_start_ts(self.grid_origin_epoch_id - epoch_id, self.prev_epoch_length_secs, 0)
+        }
+    }
+
+    /// Re-anchor the grid so a new epoch length applies from the end of the slot running at `now`
+    /// The current epoch keeps its ID and slot
+    pub fn set_epoch_length(&mut self, epoch_length_secs: u32, now: i64) {
+        if epoch_length_secs == self.epoch_length_secs {
+            return;
+        }
+        let current_epoch_id = self.epoch_id_at(now);
+        let current_length = self.epoch_length_for(current_epoch_id);
+        self.grid_origin_ts = self.epoch_start_ts(current_epoch_id) + current_length as i64;
+        self.grid_origin_epoch_id = current_epoch_id + 1;
+        self.prev_epoch_length_secs = current_length;
+        self.epoch_length_secs = epoch_length_secs;
+    }
+}
+
+/// Policy for epochs where the winning side has no stake
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OneSidedPolicy {
+    /// Refund every stake in full
+    Refund,
+    /// Carry the losing pool into the next epoch of the market
+    Rollover,
 }
 
 /// Epoch status enumeration
@@ -92,12 +337,14 @@ pub enum BetSide {
     Down = 1,
 }
 
-/// Represents a single 5-minute betting round for an asset
-/// PDA: ["epoch", asset_symbol, epoch_id (u64 in BE bytes)]
+/// Represents a single betting round of a market
+/// PDA: ["epoch", asset_symbol, timeframe, epoch_id (u64 in BE bytes)]
 #[account]
 pub struct Epoch {
     /// Asset symbol this epoch is for
     pub asset_symbol: String,
+    /// Timeframe of the market this epoch belongs to
+    pub timeframe: String,
     /// Sequential epoch identifier
     pub epoch_id: u64,
     /// Timestamp when epoch started (betting opens)
@@ -106,6 +353,12 @@ pub struct Epoch {
     pub cutoff_ts: i64,
     /// Timestamp when epoch ends and can be settled
     pub end_ts: i64,
+    /// Reference price captured when the epoch starts (i64 mantissa from Pyth, 0 until captured)
+    pub start_price: i64,
+    /// Reference price exponent (i32 from Pyth)
+    pub start_expo: i32,
+    /// Pyth publish time of the reference price
+    pub start_publish_ts: i64,
     /// Settlement price (i64 mantissa from Pyth)
     pub settle_price: i64,
     /// Settlement price exponent (i32 from Pyth)
@@ -118,6 +371,16 @@ pub struct Epoch {
     pub sum_up: u64,
     /// Total USDC bet on Down
     pub sum_down: u64,
+    /// USDC carried in from earlier one-sided epochs, added to the net pool
+    pub rollover_in: u64,
+    /// USDC carried out to the next epoch at settlement
+    pub rollover_out: u64,
+    /// Whether the stakes were rolled over instead of refunded
+    pub rolled_over: bool,
+    /// Protocol fee charged at settlement (payouts use this, not the live config)
+    pub fee_bps: u16,
+    /// Whether the epoch has become the market's active epoch (false while pre-created)
+    pub started: bool,
     /// USDC mint for this epoch
     pub mint: Pubkey,
     /// Bump seed for PDA derivation
@@ -127,20 +390,81 @@ pub struct Epoch {
 impl Epoch {
     pub const LEN: usize = 8 + // discriminator
         4 + AssetConfig::MAX_SYMBOL_LEN + // asset_symbol
+        4 + Market::MAX_TIMEFRAME_LEN + // timeframe
         8 + // epoch_id
         8 + // start_ts
         8 + // cutoff_ts
         8 + // end_ts
+        8 + // start_price
+        4 + // start_expo
+        8 + // start_publish_ts
         8 + // settle_price
         4 + // settle_expo
         1 + 1 + // status (enum: 1 discriminant + max variant size)
         1 + 1 + // winning_side (enum)
         8 + // sum_up
         8 + // sum_down
+        8 + // rollover_in
+        8 + // rollover_out
+        1 + // rolled_over
+        2 + // fee_bps
+        1 + // started
         32 + // mint
         1; // bump
 
-    /// Calculate net pool after fees
+    /// Whether a validated start price has been captured (oracle prices are always positive)
+    pub fn has_start_price(&self) -> bool {
+        self.start_price > 0
+    }
+
+    /// Compare the settlement price against the start price to pick the winning side
+    /// Prices are normalised to the smaller exponent before comparing; equal prices tie
+    pub fn determine_winner(&self) -> Result<WinningSide> {
+        let start = normalize_price(self.start_price, self.start_expo, self.settle_expo)?;
+        let settle = normalize_price(self.settle_price, self.settle_expo, self.start_expo)?;
+
+        Ok(match settle.cmp(&start) {
+            std::cmp::Ordering::Greater => WinningSide::Up,
+            std::cmp::Ordering::Less => WinningSide::Down,
+            std::cmp::Ordering::Equal => WinningSide::None,
+        })
+    }
+
+    /// Whether nobody bet on one of the two sides
+    pub fn is_one_sided(&self) -> bool {
+        self.sum_up == 0 || self.sum_down == 0
+    }
+
+    /// Total USDC staked on the winning side
+    pub fn winning_pool(&self) -> u64 {
+        match self.winning_side {
+            WinningSide::Up => self.sum_up,
+            WinningSide::Down => self.sum_down,
+            WinningSide::None => 0,
+        }
+    }
+
+    /// Whether the epoch was migrated from before timeframe markets (empty timeframe)
+    pub fn is_legacy(&self) -> bool {
+        self.timeframe.is_empty()
+    }
+
+    /// Whether stakes are returned in full instead of paid out
+    /// True for Invalid epochs, ties, and one-sided epochs that were not rolled over
+    /// Settled legacy epochs keep the old payout rules, since their fee has already left the vault
+    pub fn is_refundable(&self) -> bool {
+        match self.status {
+            EpochStatus::Invalid => true,
+            EpochStatus::Settled => {
+                !self.is_legacy()
+                    && !self.rolled_over
+                    && (self.winning_side == WinningSide::None || self.is_one_sided())
+            }
+            _ => false,
+        }
+    }
+
+    /// Calculate net pool after fees, including any rolled-over USDC
     pub fn net_pool(&self, fee_bps: u16) -> Result<u64> {
         let total = (self.sum_up as u128)
             .checked_add(self.sum_down as u128)
@@ -148,6 +472,7 @@ impl Epoch {
 
         let fee = (total * fee_bps as u128) / 10_000u128;
         let net = total.checked_sub(fee)
+            .and_then(|net| net.checked_add(self.rollover_in as u128))
             .ok_or(error!(crate::errors::BettingError::Overflow))?;
 
         Ok(net as u64)
@@ -166,11 +491,7 @@ impl Epoch {
     /// Calculate payout for a winning bet
     pub fn calculate_payout(&self, stake: u64, fee_bps: u16) -> Result<u64> {
         let net_pool = self.net_pool(fee_bps)? as u128;
-        let pool_winning_side = match self.winning_side {
-            WinningSide::Up => self.sum_up,
-            WinningSide::Down => self.sum_down,
-            WinningSide::None => return Ok(0),
-        } as u128;
+        let pool_winning_side = self.winning_pool() as u128;
 
         if pool_winning_side == 0 {
             return Ok(0);
@@ -187,20 +508,22 @@ impl Epoch {
     }
 }
 
-/// Represents a user's bet in a specific epoch
-/// PDA: ["bet", user, asset_symbol, epoch_id (u64 in BE bytes)]
+/// Represents a user's position in a specific epoch (stake can sit on both sides)
+/// PDA: ["bet", user, asset_symbol, timeframe, epoch_id (u64 in BE bytes)]
 #[account]
 pub struct UserBet {
     /// User who placed the bet
     pub user: Pubkey,
     /// Asset symbol
     pub asset_symbol: String,
+    /// Market timeframe
+    pub timeframe: String,
     /// Epoch ID
     pub epoch_id: u64,
-    /// Side of the bet (Up or Down)
-    pub side: BetSide,
-    /// Amount staked in USDC
-    pub stake: u64,
+    /// Amount staked on Up in USDC
+    pub stake_up: u64,
+    /// Amount staked on Down in USDC
+    pub stake_down: u64,
     /// Whether the user has claimed their winnings
     pub claimed: bool,
     /// Bump seed for PDA derivation
@@ -208,6 +531,261 @@ pub struct UserBet {
 }
 
 impl UserBet {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // user
+        4 + AssetConfig::MAX_SYMBOL_LEN + // asset_symbol
+        4 + Market::MAX_TIMEFRAME_LEN + // timeframe
+        8 + // epoch_id
+        8 + // stake_up
+        8 + // stake_down
+        1 + // claimed
+        1; // bump
+
+    /// Stake held on one side
+    pub fn stake_on(&self, side: BetSide) -> u64 {
+        match side {
+            BetSide::Up => self.stake_up,
+            BetSide::Down => self.stake_down,
+        }
+    }
+
+    /// Stake on the side that won (zero for ties and unsettled epochs)
+    pub fn winning_stake(&self, winning_side: &WinningSide) -> u64 {
+        match winning_side {
+            WinningSide::Up => self.stake_up,
+            WinningSide::Down => self.stake_down,
+            WinningSide::None => 0,
+        }
+    }
+
+    /// Stake across both sides
+    pub fn total_stake(&self) -> Result<u64> {
+        self.stake_up
+            .checked_add(self.stake_down)
+            .ok_or(error!(crate::errors::BettingError::Overflow))
+    }
+}
+
+/// GlobalConfig layout written before roles and timelocks; upgraded in place by migrate_config
+/// PDA: ["config"]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyGlobalConfig {
+    pub admin: Pubkey,
+    pub treasury: Pubkey,
+    pub fee_bps: u16,
+    pub settle_tip_lamports: u64,
+    pub cutoff_secs: u32,
+    pub epoch_length_secs: u32,
+    pub paused: bool,
+    pub bump: u8,
+}
+
+impl LegacyGlobalConfig {
+    pub const LEN: usize = 8 + // discriminator
+        32 + // admin
+        32 + // treasury
+        2 + // fee_bps
+        8 + // settle_tip_lamports
+        4 + // cutoff_secs
+        4 + // epoch_length_secs
+        1 + // paused
+        1; // bump
+
+    /// Decode an old-layout config account (discriminator included)
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(data.len() == Self::LEN, ErrorCode::AccountDidNotDeserialize);
+        require!(
+            data[..8] == GlobalConfig::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    /// Convert to the current layout, as initialize would have set it up
+    /// Every role stays with the admin, nothing is timelocked, and the current fee is kept as
+    /// legacy_fee_bps for the epochs the old program settled
+    pub fn into_config(self, pyth_program: Pubkey) -> GlobalConfig {
+        GlobalConfig {
+            admin: self.admin,
+            treasury: self.treasury,
+            pyth_program,
+            fee_bps: self.fee_bps,
+            settle_tip_lamports: self.settle_tip_lamports,
+            cutoff_secs: self.cutoff_secs,
+            epoch_length_secs: self.epoch_length_secs,
+            paused: self.paused,
+            bump: self.bump,
+            pending_admin: Pubkey::default(),
+            pauser: self.admin,
+            fee_manager: self.admin,
+            asset_manager: self.admin,
+            timelock_secs: 0,
+            next_change_id: 0,
+            keeper_fee_bps: 0,
+            max_future_epochs: 0,
+            cancel_fee_bps: 0,
+            legacy_fee_bps: self.fee_bps,
+        }
+    }
+}
+
+/// AssetConfig layout written before oracle sources and markets; upgraded in place by migrate_asset
+/// PDA: ["asset", asset_symbol]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyAssetConfig {
+    pub asset_symbol: String,
+    pub pyth_price_account: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub active_epoch_id: u64,
+    pub bump: u8,
+}
+
+impl LegacyAssetConfig {
+    pub const LEN: usize = 8 + // discriminator
+        4 + AssetConfig::MAX_SYMBOL_LEN + // asset_symbol
+        32 + // pyth_price_account
+        32 + // usdc_mint
+        8 + // active_epoch_id
+        1; // bump
+
+    /// Decode an old-layout asset account (discriminator included)
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(data.len() == Self::LEN, ErrorCode::AccountDidNotDeserialize);
+        require!(
+            data[..8] == AssetConfig::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    /// Convert to the current layout with the defaults set_asset_feed gives a new asset
+    /// The feed stays a legacy Pyth account; `vault` is the old vault PDA if one holds funds
+    pub fn into_asset_config(self, vault: Pubkey) -> AssetConfig {
+        let unused_source = OracleSource {
+            kind: OracleKind::PythLegacy,
+            account: Pubkey::default(),
+            feed_id: [0; 32],
+        };
+        AssetConfig {
+            asset_symbol: self.asset_symbol,
+            oracle_kind: OracleKind::PythLegacy,
+            pyth_price_account: self.pyth_price_account,
+            feed_id: [0; 32],
+            usdc_mint: self.usdc_mint,
+            vault,
+            one_sided_policy: OneSidedPolicy::Refund,
+            max_price_age_secs: AssetConfig::DEFAULT_MAX_PRICE_AGE_SECS,
+            max_conf_bps: AssetConfig::DEFAULT_MAX_CONF_BPS,
+            required_status: PriceStatus::Trading,
+            secondary_oracles: [unused_source; AssetConfig::MAX_SECONDARY_ORACLES],
+            secondary_oracle_count: 0,
+            quorum_rule: QuorumRule::Median,
+            quorum_tolerance_bps: AssetConfig::DEFAULT_QUORUM_TOLERANCE_BPS,
+            grid_offset_secs: 0,
+            min_bet: 0,
+            max_bet: 0,
+            max_user_stake_per_epoch: 0,
+            max_epoch_pool: 0,
+            market_count: 0,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Epoch layout written before timeframe markets; upgraded in place by migrate_epoch
+/// PDA: ["epoch", asset_symbol, epoch_id (u64 in BE bytes)], the same address as an empty timeframe
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyEpoch {
+    pub asset_symbol: String,
+    pub epoch_id: u64,
+    pub start_ts: i64,
+    pub cutoff_ts: i64,
+    pub end_ts: i64,
+    pub settle_price: i64,
+    pub settle_expo: i32,
+    pub status: EpochStatus,
+    pub winning_side: WinningSide,
+    pub sum_up: u64,
+    pub sum_down: u64,
+    pub mint: Pubkey,
+    pub bump: u8,
+}
+
+impl LegacyEpoch {
+    pub const LEN: usize = 8 + // discriminator
+        4 + AssetConfig::MAX_SYMBOL_LEN + // asset_symbol
+        8 + // epoch_id
+        8 + // start_ts
+        8 + // cutoff_ts
+        8 + // end_ts
+        8 + // settle_price
+        4 + // settle_expo
+        1 + 1 + // status (enum: 1 discriminant + max variant size)
+        1 + 1 + // winning_side (enum)
+        8 + // sum_up
+        8 + // sum_down
+        32 + // mint
+        1; // bump
+
+    /// Decode an old-layout epoch account (discriminator included)
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(data.len() == Self::LEN, ErrorCode::AccountDidNotDeserialize);
+        require!(
+            data[..8] == Epoch::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
+
+    /// Convert to the current layout under an empty timeframe
+    /// Unsettled epochs have no market to settle them and become refundable; settled ones keep
+    /// their result and pay out at `fee_bps`, the config's legacy_fee_bps snapshot
+    pub fn into_epoch(self, fee_bps: u16) -> Epoch {
+        let (status, fee_bps) = match self.status {
+            EpochStatus::Settled => (EpochStatus::Settled, fee_bps),
+            _ => (EpochStatus::Invalid, 0),
+        };
+        Epoch {
+            asset_symbol: self.asset_symbol,
+            timeframe: String::new(),
+            epoch_id: self.epoch_id,
+            start_ts: self.start_ts,
+            cutoff_ts: self.cutoff_ts,
+            end_ts: self.end_ts,
+            start_price: 0,
+            start_expo: 0,
+            start_publish_ts: 0,
+            settle_price: self.settle_price,
+            settle_expo: self.settle_expo,
+            status,
+            winning_side: self.winning_side,
+            sum_up: self.sum_up,
+            sum_down: self.sum_down,
+            rollover_in: 0,
+            rollover_out: 0,
+            rolled_over: false,
+            fee_bps,
+            started: true,
+            mint: self.mint,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Single-sided UserBet layout written before timeframe markets; upgraded in place by migrate_bet
+/// PDA: ["bet", user, asset_symbol, epoch_id (u64 in BE bytes)], the same address as an empty timeframe
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct LegacyUserBet {
+    pub user: Pubkey,
+    pub asset_symbol: String,
+    pub epoch_id: u64,
+    pub side: BetSide,
+    pub stake: u64,
+    pub claimed: bool,
+    pub bump: u8,
+}
+
+impl LegacyUserBet {
     pub const LEN: usize = 8 + // discriminator
         32 + // user
         4 + AssetConfig::MAX_SYMBOL_LEN + // asset_symbol
@@ -216,14 +794,652 @@ impl UserBet {
         8 + // stake
         1 + // claimed
         1; // bump
+
+    /// Decode an old-layout bet account (discriminator included)
+    pub fn from_account_data(data: &[u8]) -> Result<Self> {
+        require!(data.len() == Self::LEN, ErrorCode::AccountDidNotDeserialize);
+        require!(
+            data[..8] == UserBet::DISCRIMINATOR,
+            ErrorCode::AccountDiscriminatorMismatch
+        );
+        Ok(Self::deserialize(&mut &data[8..])?)
+    }
 }
 
-/// Helper function to convert epoch grid timestamp to epoch ID
-pub fn ts_to_epoch_id(ts: i64, epoch_length_secs: u32) -> u64 {
-    (ts / epoch_length_secs as i64) as u64
+impl From<LegacyUserBet> for UserBet {
+    fn from(legacy: LegacyUserBet) -> Self {
+        let (stake_up, stake_down) = match legacy.side {
+            BetSide::Up => (legacy.stake, 0),
+            BetSide::Down => (0, legacy.stake),
+        };
+        UserBet {
+            user: legacy.user,
+            asset_symbol: legacy.asset_symbol,
+            timeframe: String::new(),
+            epoch_id: legacy.epoch_id,
+            stake_up,
+            stake_down,
+            claimed: legacy.claimed,
+            bump: legacy.bump,
+        }
+    }
 }
 
-/// Helper function to convert epoch ID to start timestamp
-pub fn epoch_id_to_start_ts(epoch_id: u64, epoch_length_secs: u32) -> i64 {
-    (epoch_id as i64) * (epoch_length_secs as i64)
+/// Rescale a price mantissa to the smaller of its own and `other_expo`
+pub fn normalize_price(price: i64, expo: i32, other_expo: i32) -> Result<i128> {
+    let shift = expo.saturating_sub(other_expo).max(0) as u32;
+    10i128
+        .checked_pow(shift)
+        .and_then(|scale| (price as i128).checked_mul(scale))
+        .ok_or(error!(crate::errors::BettingError::Overflow))
+}
+
+/// Admin-controlled price feed for local testing (mock-oracle builds only)
+/// PDA: ["mock_price", asset_symbol]
+#[cfg(feature = "mock-oracle")]
+#[account]
+pub struct MockPrice {
+    /// Price mantissa
+    pub price: i64,
+    /// Confidence interval (same exponent)
+    pub conf: u64,
+    /// Price exponent
+    pub expo: i32,
+    /// Unix timestamp the price describes
+    pub publish_time: i64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+#[cfg(feature = "mock-oracle")]
+impl MockPrice {
+    pub const LEN: usize = 8 + // discriminator
+        8 + // price
+        8 + // conf
+        4 + // expo
+        8 + // publish_time
+        1; // bump
+}
+
+/// A sensitive parameter change waiting out the governance timelock
+/// PDA: ["pending_change", change_id]
+#[account]
+pub struct PendingChange {
+    /// Sequential ID taken from GlobalConfig.next_change_id
+    pub change_id: u64,
+    /// The change to apply on execution
+    pub change: GovernanceChange,
+    /// Admin who queued the change (receives the rent back)
+    pub proposer: Pubkey,
+    /// When the change was queued
+    pub queued_at: i64,
+    /// Earliest time the change can be executed
+    pub eta: i64,
+    /// Bump seed for PDA derivation
+    pub bump: u8,
+}
+
+impl PendingChange {
+    pub const LEN: usize = 8 + // discriminator
+        8 + // change_id
+        GovernanceChange::LEN + // change
+        32 + // proposer
+        8 + // queued_at
+        8 + // eta
+        1; // bump
+}
+
+/// Change that can be queued behind the timelock
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum GovernanceChange {
+    /// Global protocol parameters (update_config)
+    Config(ConfigChange),
+    /// An existing asset's oracle feeds, price guards and mint (set_asset_feed/update_asset)
+    AssetOracle(AssetOracleChange),
+    /// A market's timing and fee override (update_market)
+    Market(MarketChange),
+}
+
+impl GovernanceChange {
+    pub const LEN: usize = 1 + // variant tag
+        max_len(max_len(ConfigChange::LEN, AssetOracleChange::LEN), MarketChange::LEN);
+}
+
+const fn max_len(a: usize, b: usize) -> usize {
+    if a > b { a } else { b }
+}
+
+/// Global parameter update (only provided fields change)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ConfigChange {
+    pub fee_bps: Option<u16>,
+    pub settle_tip_lamports: Option<u64>,
+    pub cutoff_secs: Option<u32>,
+    pub epoch_length_secs: Option<u32>,
+    pub treasury: Option<Pubkey>,
+    pub timelock_secs: Option<u32>,
+    pub keeper_fee_bps: Option<u16>,
+    pub max_future_epochs: Option<u16>,
+    pub cancel_fee_bps: Option<u16>,
+}
+
+impl ConfigChange {
+    pub const LEN: usize = (1 + 2) + // fee_bps
+        (1 + 8) + // settle_tip_lamports
+        (1 + 4) + // cutoff_secs
+        (1 + 4) + // epoch_length_secs
+        (1 + 32) + // treasury
+        (1 + 4) + // timelock_secs
+        (1 + 2) + // keeper_fee_bps
+        (1 + 2) + // max_future_epochs
+        (1 + 2); // cancel_fee_bps
+
+    /// Fee hikes and timelock reductions must wait out the timelock
+    pub fn is_sensitive(&self, config: &GlobalConfig) -> bool {
+        matches!(self.fee_bps, Some(fee_bps) if fee_bps > config.fee_bps)
+            || matches!(self.cancel_fee_bps, Some(fee_bps) if fee_bps > config.cancel_fee_bps)
+            || matches!(self.timelock_secs, Some(timelock_secs) if timelock_secs < config.timelock_secs)
+    }
+}
+
+/// Oracle, price guard, settlement policy and mint update for an existing asset (only provided fields change)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct AssetOracleChange {
+    pub asset_symbol: String,
+    pub pyth_price_account: Option<Pubkey>,
+    pub usdc_mint: Option<Pubkey>,
+    pub oracle_kind: Option<OracleKind>,
+    pub feed_id: Option<[u8; 32]>,
+    pub secondary_oracles: Option<Vec<OracleSource>>,
+    pub max_price_age_secs: Option<u32>,
+    pub max_conf_bps: Option<u16>,
+    pub required_status: Option<PriceStatus>,
+    pub quorum_rule: Option<QuorumRule>,
+    pub quorum_tolerance_bps: Option<u16>,
+    pub one_sided_policy: Option<OneSidedPolicy>,
+}
+
+impl AssetOracleChange {
+    pub const LEN: usize = 4 + AssetConfig::MAX_SYMBOL_LEN + // asset_symbol
+        (1 + 32) + // pyth_price_account
+        (1 + 32) + // usdc_mint
+        (1 + 1) + // oracle_kind
+        (1 + 32) + // feed_id
+        (1 + 4 + OracleSource::LEN * AssetConfig::MAX_SECONDARY_ORACLES) + // secondary_oracles
+        (1 + 4) + // max_price_age_secs
+        (1 + 2) + // max_conf_bps
+        (1 + 1) + // required_status
+        (1 + 1) + // quorum_rule
+        (1 + 2) + // quorum_tolerance_bps
+        (1 + 1); // one_sided_policy
+
+    /// Any change that differs from the asset's current oracle setup, price guards or one-sided policy
+    /// must wait out the timelock
+    pub fn is_sensitive(&self, asset_config: &AssetConfig) -> bool {
+        let secondary = &asset_config.secondary_oracles[..asset_config.secondary_oracle_count as usize];
+        matches!(self.pyth_price_account, Some(account) if account != asset_config.pyth_price_account)
+            || matches!(self.usdc_mint, Some(mint) if mint != asset_config.usdc_mint)
+            || matches!(self.oracle_kind, Some(kind) if kind != asset_config.oracle_kind)
+            || matches!(self.feed_id, Some(feed_id) if feed_id != asset_config.feed_id)
+            || matches!(&self.secondary_oracles, Some(sources) if sources.as_slice() != secondary)
+            || matches!(self.max_price_age_secs, Some(age) if age != asset_config.max_price_age_secs)
+            || matches!(self.max_conf_bps, Some(bps) if bps != asset_config.max_conf_bps)
+            || matches!(self.required_status, Some(status) if status != asset_config.required_status)
+            || matches!(self.quorum_rule, Some(rule) if rule != asset_config.quorum_rule)
+            || matches!(self.quorum_tolerance_bps, Some(bps) if bps != asset_config.quorum_tolerance_bps)
+            || matches!(self.one_sided_policy, Some(policy) if policy != asset_config.one_sided_policy)
+    }
+}
+
+/// Timing and fee update for an existing market (only provided fields change)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Default)]
+pub struct MarketChange {
+    pub asset_symbol: String,
+    pub timeframe: String,
+    pub epoch_length_secs: Option<u32>,
+    pub cutoff_secs: Option<u32>,
+    pub fee_bps: Option<u16>,
+    /// Drop the fee override so the market follows GlobalConfig.fee_bps again
+    pub clear_fee_bps: bool,
+}
+
+impl MarketChange {
+    pub const LEN: usize = 4 + AssetConfig::MAX_SYMBOL_LEN + // asset_symbol
+        4 + Market::MAX_TIMEFRAME_LEN + // timeframe
+        (1 + 4) + // epoch_length_secs
+        (1 + 4) + // cutoff_secs
+        (1 + 2) + // fee_bps
+        1; // clear_fee_bps
+
+    /// Fee override after the change (None = follow the global fee)
+    pub fn new_fee_bps(&self, market: &Market) -> Option<u16> {
+        if self.clear_fee_bps {
+            None
+        } else {
+            self.fee_bps.or(market.fee_bps)
+        }
+    }
+
+    /// Raising the market's effective fee must wait out the timelock
+    pub fn is_sensitive(&self, market: &Market, config: &GlobalConfig) -> bool {
+        self.new_fee_bps(market).unwrap_or(config.fee_bps) > market.effective_fee_bps(config)
+    }
+}
+
+/// Helper function to convert a timestamp to the epoch ID of a grid shifted by `grid_offset_secs`
+/// Offsets wrap at the epoch length; timestamps before the first slot map to epoch 0
+pub fn ts_to_epoch_id(ts: i64, epoch_length_secs: u32, grid_offset_secs: u32) -> u64 {
+    let length = epoch_length_secs as i64;
+    let offset = grid_offset_secs as i64 % length;
+    (ts - offset).div_euclid(length).max(0) as u64
+}
+
+/// Helper function to convert an epoch ID to its start timestamp on a grid shifted by `grid_offset_secs`
+pub fn epoch_id_to_start_ts(epoch_id: u64, epoch_length_secs: u32, grid_offset_secs: u32) -> i64 {
+    let length = epoch_length_secs as i64;
+    (epoch_id as i64) * length + grid_offset_secs as i64 % length
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn market(epoch_length_secs: u32, grid_offset_secs: u32) -> Market {
+        Market {
+            asset_symbol: "BTCUSD".to_string(),
+            timeframe: "5m".to_string(),
+            epoch_length_secs,
+            cutoff_secs: 30,
+            fee_bps: None,
+            grid_origin_ts: epoch_id_to_start_ts(0, epoch_length_secs, grid_offset_secs),
+            grid_origin_epoch_id: 0,
+            prev_epoch_length_secs: epoch_length_secs,
+            active_epoch_id: 0,
+            last_epoch_id: 0,
+            pending_rollover: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn zero_offset_matches_plain_division() {
+        for ts in [0, 1, 299, 300, 301, 1_700_000_123] {
+            assert_eq!(ts_to_epoch_id(ts, 300, 0), (ts / 300) as u64);
+        }
+        assert_eq!(epoch_id_to_start_ts(5_666_667, 300, 0), 1_700_000_100);
+    }
+
+    #[test]
+    fn offset_shifts_boundaries() {
+        assert_eq!(ts_to_epoch_id(316, 300, 17), 0);
+        assert_eq!(ts_to_epoch_id(317, 300, 17), 1);
+        assert_eq!(ts_to_epoch_id(616, 300, 17), 1);
+        assert_eq!(ts_to_epoch_id(617, 300, 17), 2);
+        assert_eq!(epoch_id_to_start_ts(0, 300, 17), 17);
+        assert_eq!(epoch_id_to_start_ts(2, 300, 17), 617);
+    }
+
+    #[test]
+    fn offset_wraps_at_epoch_length() {
+        for ts in [0, 17, 316, 317, 1_700_000_000] {
+            assert_eq!(ts_to_epoch_id(ts, 300, 317), ts_to_epoch_id(ts, 300, 17));
+            assert_eq!(ts_to_epoch_id(ts, 300, 300), ts_to_epoch_id(ts, 300, 0));
+            assert_eq!(ts_to_epoch_id(ts, 300, 900), ts_to_epoch_id(ts, 300, 0));
+        }
+        assert_eq!(epoch_id_to_start_ts(3, 300, 317), epoch_id_to_start_ts(3, 300, 17));
+        assert_eq!(epoch_id_to_start_ts(3, 300, u32::MAX), 900 + (u32::MAX % 300) as i64);
+    }
+
+    #[test]
+    fn negative_timestamps_clamp_to_first_epoch() {
+        assert_eq!(ts_to_epoch_id(-1, 300, 0), 0);
+        assert_eq!(ts_to_epoch_id(-301, 300, 0), 0);
+        assert_eq!(ts_to_epoch_id(i64::MIN / 2, 300, 17), 0);
+        // Before the shifted first boundary
+        assert_eq!(ts_to_epoch_id(16, 300, 17), 0);
+    }
+
+    #[test]
+    fn start_ts_round_trips() {
+        for offset in [0, 1, 17, 299, 300, 4_321] {
+            for ts in [offset as i64 % 300, 1_000, 1_699_999_999, 1_700_000_000] {
+                let epoch_id = ts_to_epoch_id(ts, 300, offset);
+                let start = epoch_id_to_start_ts(epoch_id, 300, offset);
+                assert!(start <= ts && ts < start + 300, "offset {} ts {}", offset, ts);
+            }
+        }
+    }
+
+    /// Lay out an account the way the old program wrote it: discriminator, borsh body, zero padding
+    fn legacy_account(discriminator: [u8; 8], body: &impl AnchorSerialize, len: usize) -> Vec<u8> {
+        let mut data = discriminator.to_vec();
+        body.serialize(&mut data).unwrap();
+        assert!(data.len() <= len);
+        data.resize(len, 0);
+        data
+    }
+
+    #[test]
+    fn baseline_bet_account_migrates_in_place() {
+        let user = Pubkey::new_unique();
+        let legacy = LegacyUserBet {
+            user,
+            asset_symbol: "BTCUSD".to_string(),
+            epoch_id: 5_666_667,
+            side: BetSide::Down,
+            stake: 75_000_000,
+            claimed: false,
+            bump: 254,
+        };
+        let data = legacy_account(UserBet::DISCRIMINATOR, &legacy, LegacyUserBet::LEN);
+        assert_eq!(data.len(), 80);
+
+        let decoded = LegacyUserBet::from_account_data(&data).unwrap();
+        let mut migrated = [0u8; UserBet::LEN];
+        UserBet::from(decoded).try_serialize(&mut &mut migrated[..]).unwrap();
+
+        let bet = UserBet::try_deserialize(&mut &migrated[..]).unwrap();
+        assert_eq!(bet.user, user);
+        assert_eq!(bet.asset_symbol, "BTCUSD");
+        assert_eq!(bet.timeframe, "");
+        assert_eq!(bet.epoch_id, 5_666_667);
+        assert_eq!(bet.stake_up, 0);
+        assert_eq!(bet.stake_down, 75_000_000);
+        assert_eq!(bet.winning_stake(&WinningSide::Down), 75_000_000);
+        assert!(!bet.claimed);
+        assert_eq!(bet.bump, 254);
+
+        // Wrong size or account type is rejected
+        assert!(LegacyUserBet::from_account_data(&migrated).is_err());
+        let epoch_data = legacy_account(Epoch::DISCRIMINATOR, &legacy, LegacyUserBet::LEN);
+        assert!(LegacyUserBet::from_account_data(&epoch_data).is_err());
+    }
+
+    #[test]
+    fn baseline_epoch_account_migrates_in_place() {
+        let legacy = LegacyEpoch {
+            asset_symbol: "BTCUSD".to_string(),
+            epoch_id: 5_666_667,
+            start_ts: 1_700_000_100,
+            cutoff_ts: 1_700_000_370,
+            end_ts: 1_700_000_400,
+            settle_price: 6_100_000_000_000,
+            settle_expo: -8,
+            status: EpochStatus::Settled,
+            winning_side: WinningSide::Up,
+            sum_up: 100_000_000,
+            sum_down: 200_000_000,
+            mint: Pubkey::new_unique(),
+            bump: 253,
+        };
+        let data = legacy_account(Epoch::DISCRIMINATOR, &legacy, LegacyEpoch::LEN);
+        assert_eq!(data.len(), 125);
+
+        let decoded = LegacyEpoch::from_account_data(&data).unwrap();
+        let mut migrated = [0u8; Epoch::LEN];
+        decoded.into_epoch(100).try_serialize(&mut &mut migrated[..]).unwrap();
+
+        let epoch = Epoch::try_deserialize(&mut &migrated[..]).unwrap();
+        assert!(epoch.is_legacy());
+        assert_eq!(epoch.epoch_id, 5_666_667);
+        assert_eq!(epoch.end_ts, 1_700_000_400);
+        assert_eq!(epoch.status, EpochStatus::Settled);
+        assert_eq!(epoch.fee_bps, 100);
+        assert!(!epoch.is_refundable());
+        // Same payout the old claim computed with the 1% fee
+        assert_eq!(epoch.calculate_payout(100_000_000, epoch.fee_bps).unwrap(), 297_000_000);
+    }
+
+    #[test]
+    fn baseline_config_and_asset_migrate_before_claims() {
+        let admin = Pubkey::new_unique();
+        let legacy_config = LegacyGlobalConfig {
+            admin,
+            treasury: Pubkey::new_unique(),
+            fee_bps: 250,
+            settle_tip_lamports: 5_000,
+            cutoff_secs: 30,
+            epoch_length_secs: 300,
+            paused: false,
+            bump: 254,
+        };
+        let data = legacy_account(GlobalConfig::DISCRIMINATOR, &legacy_config, LegacyGlobalConfig::LEN);
+        assert_eq!(data.len(), 92);
+
+        let pyth_program = Pubkey::new_unique();
+        let mut migrated = [0u8; GlobalConfig::LEN];
+        LegacyGlobalConfig::from_account_data(&data)
+            .unwrap()
+            .into_config(pyth_program)
+            .try_serialize(&mut &mut migrated[..])
+            .unwrap();
+        let mut config = GlobalConfig::try_deserialize(&mut &migrated[..]).unwrap();
+        assert_eq!(config.pyth_program, pyth_program);
+        assert_eq!(config.fee_bps, 250);
+        assert_eq!(config.legacy_fee_bps, 250);
+        assert_eq!([config.pauser, config.fee_manager, config.asset_manager], [admin; 3]);
+        assert_eq!(config.timelock_secs, 0);
+
+        let mint = Pubkey::new_unique();
+        let legacy_asset = LegacyAssetConfig {
+            asset_symbol: "BTCUSD".to_string(),
+            pyth_price_account: Pubkey::new_unique(),
+            usdc_mint: mint,
+            active_epoch_id: 5_666_667,
+            bump: 252,
+        };
+        let data = legacy_account(AssetConfig::DISCRIMINATOR, &legacy_asset, LegacyAssetConfig::LEN);
+        assert_eq!(data.len(), 101);
+
+        let vault = Pubkey::new_unique();
+        let mut migrated = [0u8; AssetConfig::LEN];
+        LegacyAssetConfig::from_account_data(&data)
+            .unwrap()
+            .into_asset_config(vault)
+            .try_serialize(&mut &mut migrated[..])
+            .unwrap();
+        let asset = AssetConfig::try_deserialize(&mut &migrated[..]).unwrap();
+        assert_eq!(asset.oracle_kind, OracleKind::PythLegacy);
+        assert_eq!(asset.pyth_price_account, legacy_asset.pyth_price_account);
+        assert_eq!(asset.usdc_mint, mint);
+        assert_eq!(asset.vault, vault);
+        assert_eq!(asset.market_count, 0);
+        assert!(!asset.has_duplicate_oracle_sources());
+
+        // A fee change after the upgrade does not reach epochs the old program settled
+        config.fee_bps = 1_000;
+        let legacy_epoch = LegacyEpoch {
+            asset_symbol: "BTCUSD".to_string(),
+            epoch_id: 5_666_667,
+            start_ts: 1_700_000_100,
+            cutoff_ts: 1_700_000_370,
+            end_ts: 1_700_000_400,
+            settle_price: 6_100_000_000_000,
+            settle_expo: -8,
+            status: EpochStatus::Settled,
+            winning_side: WinningSide::Down,
+            sum_up: 300_000_000,
+            sum_down: 100_000_000,
+            mint,
+            bump: 253,
+        };
+        let epoch = legacy_epoch.into_epoch(config.legacy_fee_bps);
+        assert_eq!(epoch.fee_bps, 250);
+        assert_eq!(epoch.calculate_payout(100_000_000, epoch.fee_bps).unwrap(), 390_000_000);
+    }
+
+    #[test]
+    fn legacy_settled_epochs_keep_old_payout_rules() {
+        let legacy = |status| LegacyEpoch {
+            asset_symbol: "BTCUSD".to_string(),
+            epoch_id: 1,
+            start_ts: 0,
+            cutoff_ts: 270,
+            end_ts: 300,
+            settle_price: 0,
+            settle_expo: 0,
+            status,
+            winning_side: WinningSide::Up,
+            sum_up: 100,
+            sum_down: 0,
+            mint: Pubkey::default(),
+            bump: 255,
+        };
+
+        // Unsettled epochs have no market left to settle them
+        for status in [EpochStatus::Open, EpochStatus::Locked, EpochStatus::Invalid] {
+            let epoch = legacy(status).into_epoch(100);
+            assert_eq!(epoch.status, EpochStatus::Invalid);
+            assert!(epoch.is_refundable());
+        }
+
+        // A one-sided settled epoch already paid its fee, so it is claimed rather than refunded
+        let epoch = legacy(EpochStatus::Settled).into_epoch(100);
+        assert!(epoch.is_one_sided());
+        assert!(!epoch.is_refundable());
+    }
+
+    #[test]
+    fn empty_timeframe_seed_derives_baseline_address() {
+        let user = Pubkey::new_unique();
+        let epoch_id = 5_666_667u64.to_be_bytes();
+        let baseline = Pubkey::find_program_address(
+            &[b"bet", user.as_ref(), b"BTCUSD", &epoch_id],
+            &crate::ID,
+        );
+        let current = Pubkey::find_program_address(
+            &[b"bet", user.as_ref(), b"BTCUSD", b"", &epoch_id],
+            &crate::ID,
+        );
+        assert_eq!(baseline, current);
+
+        let baseline = Pubkey::find_program_address(&[b"epoch", b"BTCUSD", &epoch_id], &crate::ID);
+        let current =
+            Pubkey::find_program_address(&[b"epoch", b"BTCUSD", b"", &epoch_id], &crate::ID);
+        assert_eq!(baseline, current);
+    }
+
+    #[test]
+    fn market_grid_honours_offset_across_length_change() {
+        let mut market = market(300, 17);
+        let now = 1_700_000_050;
+        assert_eq!(market.epoch_id_at(now), ts_to_epoch_id(now, 300, 17));
+
+        let current = market.epoch_id_at(now);
+        let current_start = market.epoch_start_ts(current);
+        market.set_epoch_length(60, now);
+
+        // The running slot keeps its ID and bounds; the new length starts at its end
+        assert_eq!(market.epoch_id_at(now), current);
+        assert_eq!(market.epoch_start_ts(current), current_start);
+        assert_eq!(market.epoch_length_for(current), 300);
+        assert_eq!(market.epoch_start_ts(current + 1), current_start + 300);
+        assert_eq!(market.epoch_id_at(current_start + 300 + 60), current + 2);
+        assert_eq!((market.epoch_start_ts(current + 2) - 17) % 60, 0);
+    }
+
+    pub(crate) fn asset_with_sources(primary: OracleSource, secondary: &[OracleSource]) -> AssetConfig {
+        let mut secondary_oracles = [primary; AssetConfig::MAX_SECONDARY_ORACLES];
+        secondary_oracles[..secondary.len()].copy_from_slice(secondary);
+        AssetConfig {
+            asset_symbol: "BTCUSD".to_string(),
+            oracle_kind: primary.kind,
+            pyth_price_account: primary.account,
+            feed_id: primary.feed_id,
+            usdc_mint: Pubkey::default(),
+            vault: Pubkey::default(),
+            one_sided_policy: OneSidedPolicy::Refund,
+            max_price_age_secs: AssetConfig::DEFAULT_MAX_PRICE_AGE_SECS,
+            max_conf_bps: AssetConfig::DEFAULT_MAX_CONF_BPS,
+            required_status: PriceStatus::Trading,
+            secondary_oracles,
+            secondary_oracle_count: secondary.len() as u8,
+            quorum_rule: QuorumRule::Median,
+            quorum_tolerance_bps: AssetConfig::DEFAULT_QUORUM_TOLERANCE_BPS,
+            grid_offset_secs: 0,
+            min_bet: 0,
+            max_bet: 0,
+            max_user_stake_per_epoch: 0,
+            max_epoch_pool: 0,
+            market_count: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn oracle_sources_must_read_distinct_feeds() {
+        let legacy = OracleSource {
+            kind: OracleKind::PythLegacy,
+            account: Pubkey::new_unique(),
+            feed_id: [0; 32],
+        };
+        let switchboard = OracleSource {
+            kind: OracleKind::Switchboard,
+            account: Pubkey::new_unique(),
+            feed_id: [0; 32],
+        };
+        let pull = OracleSource {
+            kind: OracleKind::PythPull,
+            account: Pubkey::default(),
+            feed_id: [7; 32],
+        };
+
+        assert!(!asset_with_sources(legacy, &[]).has_duplicate_oracle_sources());
+        assert!(!asset_with_sources(legacy, &[switchboard, pull]).has_duplicate_oracle_sources());
+
+        // Unused slots hold copies of the primary but are not sources
+        assert!(!asset_with_sources(legacy, &[switchboard]).has_duplicate_oracle_sources());
+
+        // A secondary repeating the primary, or another secondary
+        assert!(asset_with_sources(legacy, &[legacy]).has_duplicate_oracle_sources());
+        assert!(asset_with_sources(legacy, &[switchboard, switchboard]).has_duplicate_oracle_sources());
+
+        // Pull sources are the same feed when the feed id matches, whatever the account field says
+        let other_pull = OracleSource { account: Pubkey::new_unique(), ..pull };
+        assert!(asset_with_sources(pull, &[other_pull]).has_duplicate_oracle_sources());
+        let pull_b = OracleSource { feed_id: [8; 32], ..pull };
+        assert!(!asset_with_sources(pull, &[pull_b]).has_duplicate_oracle_sources());
+    }
+
+    #[test]
+    fn price_guard_changes_wait_out_the_timelock() {
+        let primary = OracleSource {
+            kind: OracleKind::PythLegacy,
+            account: Pubkey::new_unique(),
+            feed_id: [0; 32],
+        };
+        let asset_config = asset_with_sources(primary, &[]);
+        let change = |edit: fn(&mut AssetOracleChange)| {
+            let mut change = AssetOracleChange {
+                asset_symbol: "BTCUSD".to_string(),
+                pyth_price_account: None,
+                usdc_mint: None,
+                oracle_kind: None,
+                feed_id: None,
+                secondary_oracles: None,
+                max_price_age_secs: None,
+                max_conf_bps: None,
+                required_status: None,
+                quorum_rule: None,
+                quorum_tolerance_bps: None,
+                one_sided_policy: None,
+            };
+            edit(&mut change);
+            change.is_sensitive(&asset_config)
+        };
+
+        assert!(!change(|_| {}));
+        // Restating the current guards is not a change
+        assert!(!change(|c| c.max_price_age_secs = Some(AssetConfig::DEFAULT_MAX_PRICE_AGE_SECS)));
+        assert!(!change(|c| c.required_status = Some(PriceStatus::Trading)));
+        assert!(!change(|c| c.one_sided_policy = Some(OneSidedPolicy::Refund)));
+
+        assert!(change(|c| c.max_price_age_secs = Some(3_600)));
+        assert!(change(|c| c.max_conf_bps = Some(10_000)));
+        assert!(change(|c| c.required_status = Some(PriceStatus::Halted)));
+        assert!(change(|c| c.quorum_rule = Some(QuorumRule::AllAgree)));
+        assert!(change(|c| c.quorum_tolerance_bps = Some(10_000)));
+        // Rolling one-sided pools forward instead of refunding them changes who gets paid
+        assert!(change(|c| c.one_sided_policy = Some(OneSidedPolicy::Rollover)));
+    }
 }
@@ -11,10 +11,16 @@ import {
 import {
   TOKEN_PROGRAM_ID,
   createMint,
+  getAccount,
   getOrCreateAssociatedTokenAccount,
   mintTo,
 } from "@solana/spl-token";
 
+// BPF upgradeable loader; owns each program's ProgramData account
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
 describe("betting", () => {
   // Configure the client to use the local cluster
   const provider = anchor.AnchorProvider.env();
@@ -27,21 +33,55 @@ describe("betting", () => {
   let user1: Keypair;
   let user2: Keypair;
   let usdcMint: PublicKey;
-  let pythPriceAccount: Keypair; // Mock Pyth account
+  let pythProgram: Keypair; // Stand-in for the Pyth oracle program
 
   let configPda: PublicKey;
+  let programData: PublicKey;
   let assetConfigPda: PublicKey;
+  let marketPda: PublicKey;
+  let mockPricePda: PublicKey; // Admin-set price feed (mock-oracle build)
   let epochPda: PublicKey;
   let vaultPda: PublicKey;
+  let tipJarPda: PublicKey;
   let treasuryAta: PublicKey;
   let user1Ata: PublicKey;
   let user2Ata: PublicKey;
 
   const ASSET_SYMBOL = "BTCUSD";
+  const TIMEFRAME = "5m";
   const FEE_BPS = 100; // 1%
   const SETTLE_TIP_LAMPORTS = 10_000;
   const CUTOFF_SECS = 30;
   const EPOCH_LENGTH_SECS = 300;
+  const START_PRICE = 6_000_000_000_000; // 60,000 USD at expo -8
+  const PRICE_EXPO = -8;
+
+  // Mirror Market::epoch_id_at / epoch_start_ts for slots after the grid origin
+  const epochIdAt = (market: any, ts: number) =>
+    market.gridOriginEpochId.toNumber() +
+    Math.floor((ts - market.gridOriginTs.toNumber()) / market.epochLengthSecs);
+  const epochStartTs = (market: any, epochId: number) =>
+    market.gridOriginTs.toNumber() +
+    (epochId - market.gridOriginEpochId.toNumber()) * market.epochLengthSecs;
+
+  const setMockPrice = (symbol: string, price: number, publishTime: number) =>
+    program.methods
+      .setMockPrice(symbol, new anchor.BN(price), new anchor.BN(0), PRICE_EXPO, new anchor.BN(publishTime))
+      .accounts({
+        mockPrice: PublicKey.findProgramAddressSync(
+          [Buffer.from("mock_price"), Buffer.from(symbol)],
+          program.programId
+        )[0],
+        assetConfig: PublicKey.findProgramAddressSync(
+          [Buffer.from("asset"), Buffer.from(symbol)],
+          program.programId
+        )[0],
+        config: configPda,
+        admin: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
 
   before(async () => {
     // Generate keypairs
@@ -49,7 +89,7 @@ describe("betting", () => {
     treasury = Keypair.generate();
     user1 = Keypair.generate();
     user2 = Keypair.generate();
-    pythPriceAccount = Keypair.generate();
+    pythProgram = Keypair.generate();
 
     // Airdrop SOL to accounts
     await provider.connection.requestAirdrop(
@@ -130,11 +170,26 @@ describe("betting", () => {
       program.programId
     );
 
+    [programData] = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_ID
+    );
+
     [assetConfigPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("asset"), Buffer.from(ASSET_SYMBOL)],
       program.programId
     );
 
+    [mockPricePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("mock_price"), Buffer.from(ASSET_SYMBOL)],
+      program.programId
+    );
+
+    [marketPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), Buffer.from(ASSET_SYMBOL), Buffer.from(TIMEFRAME)],
+      program.programId
+    );
+
     [vaultPda] = PublicKey.findProgramAddressSync(
       [
         Buffer.from("vault"),
@@ -143,6 +198,11 @@ describe("betting", () => {
       ],
       program.programId
     );
+
+    [tipJarPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("tip_jar")],
+      program.programId
+    );
   });
 
   it("Initializes the protocol", async () => {
@@ -150,6 +210,7 @@ describe("betting", () => {
       .initialize(
         admin.publicKey,
         treasury.publicKey,
+        pythProgram.publicKey,
         FEE_BPS,
         new anchor.BN(SETTLE_TIP_LAMPORTS),
         CUTOFF_SECS,
@@ -157,15 +218,20 @@ describe("betting", () => {
       )
       .accounts({
         config: configPda,
-        payer: admin.publicKey,
+        // The local validator deploys with the provider wallet as upgrade authority
+        payer: provider.wallet.publicKey,
+        program: program.programId,
+        programData,
         systemProgram: SystemProgram.programId,
       })
-      .signers([admin])
       .rpc();
 
     const config = await program.account.globalConfig.fetch(configPda);
     expect(config.admin.toString()).to.equal(admin.publicKey.toString());
     expect(config.treasury.toString()).to.equal(treasury.publicKey.toString());
+    expect(config.pythProgram.toString()).to.equal(
+      pythProgram.publicKey.toString()
+    );
     expect(config.feeBps).to.equal(FEE_BPS);
     expect(config.paused).to.equal(false);
   });
@@ -174,13 +240,13 @@ describe("betting", () => {
     await program.methods
       .setAssetFeed(
         ASSET_SYMBOL,
-        pythPriceAccount.publicKey,
+        mockPricePda,
         usdcMint
       )
       .accounts({
         assetConfig: assetConfigPda,
         config: configPda,
-        admin: admin.publicKey,
+        authority: admin.publicKey,
         systemProgram: SystemProgram.programId,
       })
       .signers([admin])
@@ -189,29 +255,133 @@ describe("betting", () => {
     const assetConfig = await program.account.assetConfig.fetch(assetConfigPda);
     expect(assetConfig.assetSymbol).to.equal(ASSET_SYMBOL);
     expect(assetConfig.pythPriceAccount.toString()).to.equal(
-      pythPriceAccount.publicKey.toString()
+      mockPricePda.toString()
     );
   });
 
+  it("Settles the asset against the mock price feed", async () => {
+    await program.methods
+      .updateAsset(
+        ASSET_SYMBOL,
+        null, null, null, null,
+        { mock: {} },
+        null, null, null, null, null, null, null, null, null
+      )
+      .accounts({
+        assetConfig: assetConfigPda,
+        config: configPda,
+        admin: admin.publicKey,
+      })
+      .signers([admin])
+      .rpc();
+
+    const assetConfig = await program.account.assetConfig.fetch(assetConfigPda);
+    expect(assetConfig.oracleKind).to.deep.equal({ mock: {} });
+  });
+
+  it("Initializes the asset vault", async () => {
+    await program.methods
+      .initVault(ASSET_SYMBOL)
+      .accounts({
+        vault: vaultPda,
+        usdcMint,
+        assetConfig: assetConfigPda,
+        config: configPda,
+        authority: admin.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const assetConfig = await program.account.assetConfig.fetch(assetConfigPda);
+    expect(assetConfig.vault.toString()).to.equal(vaultPda.toString());
+  });
+
+  it("Rejects a mint change once the vault exists", async () => {
+    const otherMint = await createMint(provider.connection, admin, admin.publicKey, null, 6);
+
+    try {
+      await program.methods
+        .setAssetFeed(ASSET_SYMBOL, mockPricePda, otherMint)
+        .accounts({
+          assetConfig: assetConfigPda,
+          config: configPda,
+          authority: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+      expect.fail("set_asset_feed moved the mint away from a funded vault");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("MintLocked");
+    }
+
+    const assetConfig = await program.account.assetConfig.fetch(assetConfigPda);
+    expect(assetConfig.usdcMint.toString()).to.equal(usdcMint.toString());
+  });
+
+  it("Creates a timeframe market", async () => {
+    await program.methods
+      .createMarket(ASSET_SYMBOL, TIMEFRAME, null, null, null)
+      .accounts({
+        market: marketPda,
+        assetConfig: assetConfigPda,
+        config: configPda,
+        authority: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const market = await program.account.market.fetch(marketPda);
+    expect(market.timeframe).to.equal(TIMEFRAME);
+    expect(market.epochLengthSecs).to.equal(EPOCH_LENGTH_SECS);
+    expect(market.cutoffSecs).to.equal(CUTOFF_SECS);
+    expect(market.feeBps).to.equal(null);
+  });
+
+  it("Funds the settle tip jar", async () => {
+    await program.methods
+      .fundTips(new anchor.BN(LAMPORTS_PER_SOL))
+      .accounts({
+        tipJar: tipJarPda,
+        funder: admin.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([admin])
+      .rpc();
+
+    const balance = await provider.connection.getBalance(tipJarPda);
+    expect(balance).to.equal(LAMPORTS_PER_SOL);
+  });
+
   it("Creates an epoch", async () => {
+    const market = await program.account.market.fetch(marketPda);
     const now = Math.floor(Date.now() / 1000);
-    const epochId = Math.floor(now / EPOCH_LENGTH_SECS);
+    const epochId = epochIdAt(market, now);
+
+    // The start price has to describe the start of the slot
+    await setMockPrice(ASSET_SYMBOL, START_PRICE, epochStartTs(market, epochId));
 
     [epochPda] = PublicKey.findProgramAddressSync(
       [
         Buffer.from("epoch"),
         Buffer.from(ASSET_SYMBOL),
+        Buffer.from(TIMEFRAME),
         new anchor.BN(epochId).toArrayLike(Buffer, "be", 8),
       ],
       program.programId
     );
 
     await program.methods
-      .createEpoch(ASSET_SYMBOL)
+      .createEpoch(ASSET_SYMBOL, TIMEFRAME)
       .accounts({
         epoch: epochPda,
+        market: marketPda,
         assetConfig: assetConfigPda,
         config: configPda,
+        pythPriceAccount: mockPricePda,
         payer: admin.publicKey,
         systemProgram: SystemProgram.programId,
       })
@@ -220,18 +390,20 @@ describe("betting", () => {
 
     const epoch = await program.account.epoch.fetch(epochPda);
     expect(epoch.assetSymbol).to.equal(ASSET_SYMBOL);
+    expect(epoch.timeframe).to.equal(TIMEFRAME);
     expect(epoch.status).to.deep.equal({ open: {} });
+    expect(epoch.startPrice.toNumber()).to.equal(START_PRICE);
   });
 
   it("Places bets from two users", async () => {
-    const now = Math.floor(Date.now() / 1000);
-    const epochId = Math.floor(now / EPOCH_LENGTH_SECS);
+    const epochId = (await program.account.epoch.fetch(epochPda)).epochId.toNumber();
 
     const [user1BetPda] = PublicKey.findProgramAddressSync(
       [
         Buffer.from("bet"),
         user1.publicKey.toBuffer(),
         Buffer.from(ASSET_SYMBOL),
+        Buffer.from(TIMEFRAME),
         new anchor.BN(epochId).toArrayLike(Buffer, "be", 8),
       ],
       program.programId
@@ -241,6 +413,7 @@ describe("betting", () => {
     await program.methods
       .placeBet(
         ASSET_SYMBOL,
+        TIMEFRAME,
         new anchor.BN(epochId),
         { up: {} },
         new anchor.BN(100_000_000) // 100 USDC
@@ -264,6 +437,7 @@ describe("betting", () => {
         Buffer.from("bet"),
         user2.publicKey.toBuffer(),
         Buffer.from(ASSET_SYMBOL),
+        Buffer.from(TIMEFRAME),
         new anchor.BN(epochId).toArrayLike(Buffer, "be", 8),
       ],
       program.programId
@@ -273,6 +447,7 @@ describe("betting", () => {
     await program.methods
       .placeBet(
         ASSET_SYMBOL,
+        TIMEFRAME,
         new anchor.BN(epochId),
         { down: {} },
         new anchor.BN(200_000_000) // 200 USDC
@@ -296,14 +471,237 @@ describe("betting", () => {
     expect(epoch.sumDown.toString()).to.equal("200000000");
   });
 
+  it("Adds to both sides of an existing position", async () => {
+    const epoch = await program.account.epoch.fetch(epochPda);
+    const [user1BetPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("bet"),
+        user1.publicKey.toBuffer(),
+        Buffer.from(ASSET_SYMBOL),
+        Buffer.from(TIMEFRAME),
+        epoch.epochId.toArrayLike(Buffer, "be", 8),
+      ],
+      program.programId
+    );
+    const accounts = {
+      userBet: user1BetPda,
+      epoch: epochPda,
+      assetConfig: assetConfigPda,
+      config: configPda,
+      vault: vaultPda,
+      userAta: user1Ata,
+      user: user1.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+
+    // User1 adds 50 USDC to their Up stake
+    await program.methods
+      .placeBet(ASSET_SYMBOL, TIMEFRAME, epoch.epochId, { up: {} }, new anchor.BN(50_000_000))
+      .accounts(accounts)
+      .signers([user1])
+      .rpc();
+
+    // ...and hedges with 10 USDC on Down
+    await program.methods
+      .placeBet(ASSET_SYMBOL, TIMEFRAME, epoch.epochId, { down: {} }, new anchor.BN(10_000_000))
+      .accounts(accounts)
+      .signers([user1])
+      .rpc();
+
+    const userBet = await program.account.userBet.fetch(user1BetPda);
+    expect(userBet.stakeUp.toString()).to.equal("150000000");
+    expect(userBet.stakeDown.toString()).to.equal("10000000");
+    const updated = await program.account.epoch.fetch(epochPda);
+    expect(updated.sumUp.toString()).to.equal("150000000");
+    expect(updated.sumDown.toString()).to.equal("210000000");
+  });
+
+  it("Cancels a bet before cutoff", async () => {
+    const epoch = await program.account.epoch.fetch(epochPda);
+    const [user2BetPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("bet"),
+        user2.publicKey.toBuffer(),
+        Buffer.from(ASSET_SYMBOL),
+        Buffer.from(TIMEFRAME),
+        epoch.epochId.toArrayLike(Buffer, "be", 8),
+      ],
+      program.programId
+    );
+    const before = await getAccount(provider.connection, user2Ata);
+
+    await program.methods
+      .cancelBet(ASSET_SYMBOL, TIMEFRAME, epoch.epochId)
+      .accounts({
+        userBet: user2BetPda,
+        epoch: epochPda,
+        assetConfig: assetConfigPda,
+        config: configPda,
+        vault: vaultPda,
+        treasuryAta,
+        userAta: user2Ata,
+        user: user2.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([user2])
+      .rpc();
+
+    // No cancellation fee is configured, so the whole stake comes back and the bet is closed
+    const after = await getAccount(provider.connection, user2Ata);
+    expect((after.amount - before.amount).toString()).to.equal("200000000");
+    expect(await provider.connection.getAccountInfo(user2BetPda)).to.be.null;
+    const cancelled = await program.account.epoch.fetch(epochPda);
+    expect(cancelled.sumDown.toString()).to.equal("10000000");
+
+    // Bet again so the rest of the suite sees the same pool
+    await program.methods
+      .placeBet(ASSET_SYMBOL, TIMEFRAME, epoch.epochId, { down: {} }, new anchor.BN(200_000_000))
+      .accounts({
+        userBet: user2BetPda,
+        epoch: epochPda,
+        assetConfig: assetConfigPda,
+        config: configPda,
+        vault: vaultPda,
+        userAta: user2Ata,
+        user: user2.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user2])
+      .rpc();
+
+    const restored = await program.account.epoch.fetch(epochPda);
+    expect(restored.sumDown.toString()).to.equal("210000000");
+  });
+
+  it("Rejects a bet below the asset's minimum", async () => {
+    const setMinBet = (minBet: anchor.BN) =>
+      program.methods
+        .updateAsset(
+          ASSET_SYMBOL,
+          null, null, null, null, null, null, null, null, null, null,
+          minBet, null, null, null
+        )
+        .accounts({
+          assetConfig: assetConfigPda,
+          config: configPda,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+    await setMinBet(new anchor.BN(1_000_000)); // 1 USDC
+
+    const epoch = await program.account.epoch.fetch(epochPda);
+    const [user1BetPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("bet"),
+        user1.publicKey.toBuffer(),
+        Buffer.from(ASSET_SYMBOL),
+        Buffer.from(TIMEFRAME),
+        epoch.epochId.toArrayLike(Buffer, "be", 8),
+      ],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .placeBet(ASSET_SYMBOL, TIMEFRAME, epoch.epochId, { up: {} }, new anchor.BN(500_000))
+        .accounts({
+          userBet: user1BetPda,
+          epoch: epochPda,
+          assetConfig: assetConfigPda,
+          config: configPda,
+          vault: vaultPda,
+          userAta: user1Ata,
+          user: user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc();
+      expect.fail("place_bet accepted a bet below the minimum");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("BetTooSmall");
+    }
+
+    await setMinBet(new anchor.BN(0));
+  });
+
+  it("Skips unclaimable pairs in a batch claim", async () => {
+    const epoch = await program.account.epoch.fetch(epochPda);
+    const [user1BetPda] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("bet"),
+        user1.publicKey.toBuffer(),
+        Buffer.from(ASSET_SYMBOL),
+        Buffer.from(TIMEFRAME),
+        epoch.epochId.toArrayLike(Buffer, "be", 8),
+      ],
+      program.programId
+    );
+    const before = await getAccount(provider.connection, user1Ata);
+
+    // The epoch is still open, so the pair is skipped rather than failing the batch
+    await program.methods
+      .claimMany(ASSET_SYMBOL)
+      .accounts({
+        assetConfig: assetConfigPda,
+        config: configPda,
+        vault: vaultPda,
+        userAta: user1Ata,
+        user: user1.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([
+        { pubkey: epochPda, isSigner: false, isWritable: false },
+        { pubkey: user1BetPda, isSigner: false, isWritable: true },
+      ])
+      .signers([user1])
+      .rpc();
+
+    const after = await getAccount(provider.connection, user1Ata);
+    expect(after.amount.toString()).to.equal(before.amount.toString());
+    const userBet = await program.account.userBet.fetch(user1BetPda);
+    expect(userBet.claimed).to.equal(false);
+  });
+
+  it("Rejects a fee account other than the treasury ATA", async () => {
+    const epoch = await program.account.epoch.fetch(epochPda);
+
+    try {
+      await program.methods
+        .closeEpoch(ASSET_SYMBOL, TIMEFRAME, epoch.epochId)
+        .accounts({
+          epoch: epochPda,
+          market: marketPda,
+          assetConfig: assetConfigPda,
+          config: configPda,
+          vault: vaultPda,
+          treasuryAta: user1Ata, // Settler-controlled account, same mint
+          pythPriceAccount: mockPricePda,
+          tipJar: tipJarPda,
+          callerAta: user1Ata,
+          caller: user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc();
+      expect.fail("close_epoch accepted a foreign fee account");
+    } catch (err) {
+      expect(err.error.errorCode.code).to.equal("InvalidTreasuryAccount");
+    }
+  });
+
   it("Locks epoch after cutoff", async () => {
     // Wait for cutoff time (in tests, we can't easily wait, so this is demonstrative)
-    const now = Math.floor(Date.now() / 1000);
-    const epochId = Math.floor(now / EPOCH_LENGTH_SECS);
+    const epochId = (await program.account.epoch.fetch(epochPda)).epochId.toNumber();
 
     try {
       await program.methods
-        .lockEpoch(ASSET_SYMBOL, new anchor.BN(epochId))
+        .lockEpoch(ASSET_SYMBOL, TIMEFRAME, new anchor.BN(epochId))
         .accounts({
           epoch: epochPda,
           assetConfig: assetConfigPda,
@@ -325,7 +723,7 @@ describe("betting", () => {
       .pause()
       .accounts({
         config: configPda,
-        admin: admin.publicKey,
+        authority: admin.publicKey,
       })
       .signers([admin])
       .rpc();
@@ -345,4 +743,669 @@ describe("betting", () => {
     config = await program.account.globalConfig.fetch(configPda);
     expect(config.paused).to.equal(false);
   });
+
+  // Scripted price paths: a second asset on a short grid, priced through its mock feed
+  describe("price paths", () => {
+    const SYMBOL = "ETHUSD";
+    const SHORT_TF = "10s";
+    const SHORT_LENGTH_SECS = 10;
+    const SHORT_CUTOFF_SECS = 3;
+    const STAKE = 100_000_000; // 100 USDC
+    const MAX_PRICE_AGE_SECS = 5; // Shorter than an epoch, so a start price cannot settle it
+
+    const pda = (...seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+    const sleepUntil = (ts: number) =>
+      new Promise((resolve) =>
+        setTimeout(resolve, Math.max(0, ts * 1000 - Date.now()))
+      );
+
+    const assetPda = pda(Buffer.from("asset"), Buffer.from(SYMBOL));
+    const feedPda = pda(Buffer.from("mock_price"), Buffer.from(SYMBOL));
+    const shortMarketPda = pda(Buffer.from("market"), Buffer.from(SYMBOL), Buffer.from(SHORT_TF));
+    let ethVaultPda: PublicKey;
+
+    const epochAddress = (epochId: number) =>
+      pda(
+        Buffer.from("epoch"),
+        Buffer.from(SYMBOL),
+        Buffer.from(SHORT_TF),
+        new anchor.BN(epochId).toArrayLike(Buffer, "be", 8)
+      );
+    const betAddress = (user: Keypair, epochId: number) =>
+      pda(
+        Buffer.from("bet"),
+        user.publicKey.toBuffer(),
+        Buffer.from(SYMBOL),
+        Buffer.from(SHORT_TF),
+        new anchor.BN(epochId).toArrayLike(Buffer, "be", 8)
+      );
+
+    // Open (or start a pre-created) epoch for the current slot
+    const createEpoch = (epochId: number) =>
+      program.methods
+        .createEpoch(SYMBOL, SHORT_TF)
+        .accounts({
+          epoch: epochAddress(epochId),
+          market: shortMarketPda,
+          assetConfig: assetPda,
+          config: configPda,
+          pythPriceAccount: feedPda,
+          payer: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+
+    const placeBet = (user: Keypair, ata: PublicKey, epochId: number, side: any) =>
+      program.methods
+        .placeBet(SYMBOL, SHORT_TF, new anchor.BN(epochId), side, new anchor.BN(STAKE))
+        .accounts({
+          userBet: betAddress(user, epochId),
+          epoch: epochAddress(epochId),
+          assetConfig: assetPda,
+          config: configPda,
+          vault: ethVaultPda,
+          userAta: ata,
+          user: user.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user])
+        .rpc();
+
+    // Sleep into the next slot and return its epoch id and start time
+    const nextSlot = async () => {
+      const market = await program.account.market.fetch(shortMarketPda);
+      const epochId = epochIdAt(market, Math.floor(Date.now() / 1000)) + 1;
+      const startTs = epochStartTs(market, epochId);
+      await sleepUntil(startTs + 1);
+      return { epochId, startTs };
+    };
+
+    // Open the next slot at `startPrice` and have user1 back Up, user2 Down
+    const openWithBets = async (startPrice: number) => {
+      const { epochId, startTs } = await nextSlot();
+      await setMockPrice(SYMBOL, startPrice, startTs);
+      await createEpoch(epochId);
+      await placeBet(user1, user1Ata, epochId, { up: {} });
+      await placeBet(user2, user2Ata, epochId, { down: {} });
+      return epochId;
+    };
+
+    const closeEpoch = (epochId: number) =>
+      program.methods
+        .closeEpoch(SYMBOL, SHORT_TF, new anchor.BN(epochId))
+        .accounts({
+          epoch: epochAddress(epochId),
+          market: shortMarketPda,
+          assetConfig: assetPda,
+          config: configPda,
+          vault: ethVaultPda,
+          treasuryAta,
+          pythPriceAccount: feedPda,
+          tipJar: tipJarPda,
+          callerAta: user1Ata,
+          caller: user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([user1])
+        .rpc();
+
+    before(async () => {
+      ethVaultPda = pda(Buffer.from("vault"), usdcMint.toBuffer(), Buffer.from(SYMBOL));
+
+      await program.methods
+        .setAssetFeed(SYMBOL, feedPda, usdcMint)
+        .accounts({
+          assetConfig: assetPda,
+          config: configPda,
+          authority: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .updateAsset(
+          SYMBOL,
+          null,
+          MAX_PRICE_AGE_SECS,
+          null, null,
+          { mock: {} },
+          null, null, null, null, null, null, null, null, null
+        )
+        .accounts({ assetConfig: assetPda, config: configPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .initVault(SYMBOL)
+        .accounts({
+          vault: ethVaultPda,
+          usdcMint,
+          assetConfig: assetPda,
+          config: configPda,
+          authority: admin.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+      await program.methods
+        .createMarket(SYMBOL, SHORT_TF, SHORT_LENGTH_SECS, SHORT_CUTOFF_SECS, null)
+        .accounts({
+          market: shortMarketPda,
+          assetConfig: assetPda,
+          config: configPda,
+          authority: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("Settles Up when the price rises and pays the winner", async () => {
+      const epochId = await openWithBets(START_PRICE);
+      let epoch = await program.account.epoch.fetch(epochAddress(epochId));
+      await sleepUntil(epoch.endTs.toNumber() + 1);
+
+      await setMockPrice(SYMBOL, START_PRICE + 5_000_000_000, epoch.endTs.toNumber());
+      await closeEpoch(epochId);
+
+      epoch = await program.account.epoch.fetch(epochAddress(epochId));
+      expect(epoch.status).to.deep.equal({ settled: {} });
+      expect(epoch.winningSide).to.deep.equal({ up: {} });
+
+      const before = await getAccount(provider.connection, user1Ata);
+      await program.methods
+        .claim(SYMBOL, SHORT_TF, new anchor.BN(epochId))
+        .accounts({
+          userBet: betAddress(user1, epochId),
+          epoch: epochAddress(epochId),
+          assetConfig: assetPda,
+          config: configPda,
+          vault: ethVaultPda,
+          userAta: user1Ata,
+          user: user1.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user1])
+        .rpc();
+      const after = await getAccount(provider.connection, user1Ata);
+
+      // Both stakes less the 1% fee
+      const payout = ((2 * STAKE) * (10_000 - FEE_BPS)) / 10_000;
+      expect((after.amount - before.amount).toString()).to.equal(payout.toString());
+    });
+
+    it("Settles Down when the price falls", async () => {
+      const epochId = await openWithBets(START_PRICE);
+      let epoch = await program.account.epoch.fetch(epochAddress(epochId));
+      await sleepUntil(epoch.endTs.toNumber() + 1);
+
+      await setMockPrice(SYMBOL, START_PRICE - 5_000_000_000, epoch.endTs.toNumber());
+      await closeEpoch(epochId);
+
+      epoch = await program.account.epoch.fetch(epochAddress(epochId));
+      expect(epoch.status).to.deep.equal({ settled: {} });
+      expect(epoch.winningSide).to.deep.equal({ down: {} });
+    });
+
+    it("Invalidates the epoch on a stale settlement price and refunds stakes", async () => {
+      const epochId = await openWithBets(START_PRICE);
+      let epoch = await program.account.epoch.fetch(epochAddress(epochId));
+      await sleepUntil(epoch.endTs.toNumber() + 1);
+
+      // The feed still carries the start price, published one epoch before end_ts
+      const jarBefore = await provider.connection.getBalance(tipJarPda);
+      await closeEpoch(epochId);
+
+      epoch = await program.account.epoch.fetch(epochAddress(epochId));
+      expect(epoch.status).to.deep.equal({ invalid: {} });
+      // Invalidating an epoch earns no settle tip
+      expect(await provider.connection.getBalance(tipJarPda)).to.equal(jarBefore);
+
+      const before = await getAccount(provider.connection, user2Ata);
+      await program.methods
+        .refund(SYMBOL, SHORT_TF, new anchor.BN(epochId))
+        .accounts({
+          userBet: betAddress(user2, epochId),
+          epoch: epochAddress(epochId),
+          assetConfig: assetPda,
+          vault: ethVaultPda,
+          userAta: user2Ata,
+          user: user2.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user2])
+        .rpc();
+      const after = await getAccount(provider.connection, user2Ata);
+      expect((after.amount - before.amount).toString()).to.equal(STAKE.toString());
+    });
+
+    describe("pre-created epochs", () => {
+      const MAX_FUTURE_EPOCHS = 2;
+      let preCreatedId: number;
+
+      const createEpochAt = (epochId: number) =>
+        program.methods
+          .createEpochAt(SYMBOL, SHORT_TF, new anchor.BN(epochId))
+          .accounts({
+            epoch: epochAddress(epochId),
+            market: shortMarketPda,
+            assetConfig: assetPda,
+            config: configPda,
+            payer: admin.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc();
+
+      before(async () => {
+        await program.methods
+          .updateConfig(null, null, null, null, null, null, null, MAX_FUTURE_EPOCHS, null)
+          .accounts({ config: configPda, authority: admin.publicKey })
+          .signers([admin])
+          .rpc();
+      });
+
+      it("Pre-creates epochs only up to max_future_epochs slots ahead", async () => {
+        // Start of a slot, so the current epoch id holds for the whole test
+        const { epochId: currentId } = await nextSlot();
+
+        try {
+          await createEpochAt(currentId + MAX_FUTURE_EPOCHS + 1);
+          expect.fail("create_epoch_at went past max_future_epochs");
+        } catch (err) {
+          expect(err.error.errorCode.code).to.equal("EpochOutOfRange");
+        }
+
+        preCreatedId = currentId + MAX_FUTURE_EPOCHS;
+        await createEpochAt(preCreatedId);
+
+        const market = await program.account.market.fetch(shortMarketPda);
+        const epoch = await program.account.epoch.fetch(epochAddress(preCreatedId));
+        expect(epoch.started).to.equal(false);
+        expect(epoch.startTs.toNumber()).to.equal(epochStartTs(market, preCreatedId));
+        expect(epoch.startPrice.toNumber()).to.equal(0);
+        expect(market.lastEpochId.toNumber()).to.equal(preCreatedId);
+      });
+
+      it("Fills in a pre-created epoch's missing start price at settlement", async () => {
+        const market = await program.account.market.fetch(shortMarketPda);
+        const startTs = epochStartTs(market, preCreatedId);
+
+        // Nothing usable describes start_ts when the slot begins, so the epoch starts unpriced
+        await setMockPrice(SYMBOL, START_PRICE, startTs - 60);
+        await sleepUntil(startTs + 1);
+        await createEpoch(preCreatedId);
+
+        let epoch = await program.account.epoch.fetch(epochAddress(preCreatedId));
+        expect(epoch.started).to.equal(true);
+        expect(epoch.startPrice.toNumber()).to.equal(0);
+
+        await placeBet(user1, user1Ata, preCreatedId, { up: {} });
+        await placeBet(user2, user2Ata, preCreatedId, { down: {} });
+        await sleepUntil(epoch.endTs.toNumber() + 1);
+
+        // Published midway through the 10s slot: fresh enough for both start_ts and end_ts
+        const publishTs = startTs + SHORT_LENGTH_SECS / 2;
+        await setMockPrice(SYMBOL, START_PRICE, publishTs);
+        await closeEpoch(preCreatedId);
+
+        epoch = await program.account.epoch.fetch(epochAddress(preCreatedId));
+        expect(epoch.status).to.deep.equal({ settled: {} });
+        expect(epoch.startPrice.toNumber()).to.equal(START_PRICE);
+        expect(epoch.startPublishTs.toNumber()).to.equal(publishTs);
+        // One feed describes both ends, so the epoch ties and is refunded
+        expect(epoch.winningSide).to.deep.equal({ none: {} });
+      });
+    });
+
+    describe("crank", () => {
+      const crank = (activeEpochId: number | null, gridEpochId: number) =>
+        program.methods
+          .crank(SYMBOL, SHORT_TF)
+          .accounts({
+            activeEpoch: activeEpochId === null ? null : epochAddress(activeEpochId),
+            gridEpoch: epochAddress(gridEpochId),
+            market: shortMarketPda,
+            assetConfig: assetPda,
+            config: configPda,
+            vault: ethVaultPda,
+            treasuryAta,
+            callerAta: user1Ata,
+            pythPriceAccount: feedPda,
+            tipJar: tipJarPda,
+            caller: user1.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([user1])
+          .rpc();
+
+      const tipJarBalance = () => provider.connection.getBalance(tipJarPda);
+
+      it("Requires the due active epoch, then settles it and opens the next", async () => {
+        const epochId = await openWithBets(START_PRICE);
+        const epoch = await program.account.epoch.fetch(epochAddress(epochId));
+        await sleepUntil(epoch.endTs.toNumber() + 1);
+
+        // end_ts is also the next slot's start_ts, so one price settles this epoch and opens the next
+        await setMockPrice(SYMBOL, START_PRICE + 5_000_000_000, epoch.endTs.toNumber());
+
+        try {
+          await crank(null, epochId + 1);
+          expect.fail("crank opened the next epoch past an unsettled active epoch");
+        } catch (err) {
+          expect(err.error.errorCode.code).to.equal("ActiveEpochRequired");
+        }
+        let market = await program.account.market.fetch(shortMarketPda);
+        expect(market.activeEpochId.toNumber()).to.equal(epochId);
+
+        const jarBefore = await tipJarBalance();
+        await crank(epochId, epochId + 1);
+
+        const settled = await program.account.epoch.fetch(epochAddress(epochId));
+        expect(settled.status).to.deep.equal({ settled: {} });
+        expect(settled.winningSide).to.deep.equal({ up: {} });
+        const next = await program.account.epoch.fetch(epochAddress(epochId + 1));
+        expect(next.startPrice.toNumber()).to.equal(START_PRICE + 5_000_000_000);
+        market = await program.account.market.fetch(shortMarketPda);
+        expect(market.activeEpochId.toNumber()).to.equal(epochId + 1);
+
+        // Lock, settle and open: one tip each
+        expect(jarBefore - (await tipJarBalance())).to.equal(3 * SETTLE_TIP_LAMPORTS);
+      });
+
+      it("Pays no tip for an epoch it invalidates", async () => {
+        const market = await program.account.market.fetch(shortMarketPda);
+        const activeId = market.activeEpochId.toNumber();
+        const active = await program.account.epoch.fetch(epochAddress(activeId));
+
+        // Pre-create the following slot so it can start without a fresh price
+        await program.methods
+          .createEpochAt(SYMBOL, SHORT_TF, new anchor.BN(activeId + 1))
+          .accounts({
+            epoch: epochAddress(activeId + 1),
+            market: shortMarketPda,
+            assetConfig: assetPda,
+            config: configPda,
+            payer: admin.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([admin])
+          .rpc();
+
+        // The feed still describes start_ts, a whole slot before end_ts
+        await sleepUntil(active.endTs.toNumber() + 1);
+        const jarBefore = await tipJarBalance();
+        await crank(activeId, activeId + 1);
+
+        const invalid = await program.account.epoch.fetch(epochAddress(activeId));
+        expect(invalid.status).to.deep.equal({ invalid: {} });
+        const started = await program.account.epoch.fetch(epochAddress(activeId + 1));
+        expect(started.started).to.equal(true);
+
+        // Lock and start are tipped; the invalidating settlement is not, as in close_epoch
+        expect(jarBefore - (await tipJarBalance())).to.equal(2 * SETTLE_TIP_LAMPORTS);
+      });
+    });
+
+    describe("one-sided rollover", () => {
+      before(async () => {
+        await program.methods
+          .updateAsset(
+            SYMBOL,
+            { rollover: {} },
+            null, null, null, null, null, null, null, null, null, null, null, null, null
+          )
+          .accounts({ assetConfig: assetPda, config: configPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc();
+      });
+
+      it("Carries a pool nobody won into the next epoch and pays it to that epoch's winners", async () => {
+        // Only user2 backs Down, and the price rises
+        const { epochId: oneSidedId, startTs } = await nextSlot();
+        await setMockPrice(SYMBOL, START_PRICE, startTs);
+        await createEpoch(oneSidedId);
+        await placeBet(user2, user2Ata, oneSidedId, { down: {} });
+
+        let oneSided = await program.account.epoch.fetch(epochAddress(oneSidedId));
+        await sleepUntil(oneSided.endTs.toNumber() + 1);
+        const pendingBefore = (await program.account.market.fetch(shortMarketPda)).pendingRollover;
+        await setMockPrice(SYMBOL, START_PRICE + 5_000_000_000, oneSided.endTs.toNumber());
+        await closeEpoch(oneSidedId);
+
+        oneSided = await program.account.epoch.fetch(epochAddress(oneSidedId));
+        expect(oneSided.status).to.deep.equal({ settled: {} });
+        expect(oneSided.winningSide).to.deep.equal({ up: {} });
+        expect(oneSided.rolledOver).to.equal(true);
+        // No fee on a pool nobody won
+        expect(oneSided.rolloverOut.toNumber()).to.equal(STAKE);
+        let market = await program.account.market.fetch(shortMarketPda);
+        expect(market.pendingRollover.sub(pendingBefore).toNumber()).to.equal(STAKE);
+
+        const epochId = await openWithBets(START_PRICE);
+        let epoch = await program.account.epoch.fetch(epochAddress(epochId));
+        expect(epoch.rolloverIn.toNumber()).to.equal(market.pendingRollover.toNumber());
+        market = await program.account.market.fetch(shortMarketPda);
+        expect(market.pendingRollover.toNumber()).to.equal(0);
+
+        await sleepUntil(epoch.endTs.toNumber() + 1);
+        await setMockPrice(SYMBOL, START_PRICE + 5_000_000_000, epoch.endTs.toNumber());
+        await closeEpoch(epochId);
+        epoch = await program.account.epoch.fetch(epochAddress(epochId));
+        expect(epoch.winningSide).to.deep.equal({ up: {} });
+
+        const before = await getAccount(provider.connection, user1Ata);
+        await program.methods
+          .claim(SYMBOL, SHORT_TF, new anchor.BN(epochId))
+          .accounts({
+            userBet: betAddress(user1, epochId),
+            epoch: epochAddress(epochId),
+            assetConfig: assetPda,
+            config: configPda,
+            vault: ethVaultPda,
+            userAta: user1Ata,
+            user: user1.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([user1])
+          .rpc();
+        const after = await getAccount(provider.connection, user1Ata);
+
+        // Both stakes less the 1% fee, plus the whole carried-in pool
+        const payout = ((2 * STAKE) * (10_000 - FEE_BPS)) / 10_000 + epoch.rolloverIn.toNumber();
+        expect((after.amount - before.amount).toString()).to.equal(payout.toString());
+      });
+    });
+  });
+
+  describe("governance", () => {
+    const TIMELOCK_SECS = 3;
+
+    const pendingChangeAddress = (changeId: anchor.BN) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("pending_change"), changeId.toArrayLike(Buffer, "be", 8)],
+        program.programId
+      )[0];
+
+    // Queue an asset change that only sets the one-sided policy; returns its change id
+    const queuePolicyChange = async (policy: any) => {
+      const { nextChangeId } = await program.account.globalConfig.fetch(configPda);
+      await program.methods
+        .queueChange({
+          assetOracle: {
+            0: {
+              assetSymbol: ASSET_SYMBOL,
+              pythPriceAccount: null,
+              usdcMint: null,
+              oracleKind: null,
+              feedId: null,
+              secondaryOracles: null,
+              maxPriceAgeSecs: null,
+              maxConfBps: null,
+              requiredStatus: null,
+              quorumRule: null,
+              quorumToleranceBps: null,
+              oneSidedPolicy: policy,
+            },
+          },
+        })
+        .accounts({
+          pendingChange: pendingChangeAddress(nextChangeId),
+          config: configPda,
+          admin: admin.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([admin])
+        .rpc();
+      return nextChangeId;
+    };
+
+    const executeChange = (changeId: anchor.BN) =>
+      program.methods
+        .executeChange(changeId)
+        .accounts({
+          pendingChange: pendingChangeAddress(changeId),
+          config: configPda,
+          assetConfig: assetConfigPda,
+          market: null,
+          proposer: admin.publicKey,
+          caller: user1.publicKey,
+        })
+        .signers([user1])
+        .rpc();
+
+    before(async () => {
+      // Raising the timelock applies directly; lowering it would have to be queued
+      await program.methods
+        .updateConfig(null, null, null, null, null, TIMELOCK_SECS, null, null, null)
+        .accounts({ config: configPda, authority: admin.publicKey })
+        .signers([admin])
+        .rpc();
+    });
+
+    it("Rejects a one-sided policy change outside the queue", async () => {
+      try {
+        await program.methods
+          .updateAsset(
+            ASSET_SYMBOL,
+            { rollover: {} },
+            null, null, null, null, null, null, null, null, null, null, null, null, null
+          )
+          .accounts({ assetConfig: assetConfigPda, config: configPda, admin: admin.publicKey })
+          .signers([admin])
+          .rpc();
+        expect.fail("update_asset changed the one-sided policy without the timelock");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("TimelockRequired");
+      }
+    });
+
+    it("Executes a queued policy change only after its eta", async () => {
+      const changeId = await queuePolicyChange({ rollover: {} });
+      const pending = await program.account.pendingChange.fetch(pendingChangeAddress(changeId));
+      expect(pending.eta.toNumber() - pending.queuedAt.toNumber()).to.equal(TIMELOCK_SECS);
+
+      try {
+        await executeChange(changeId);
+        expect.fail("execute_change ran before the eta");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("TimelockNotElapsed");
+      }
+      let assetConfig = await program.account.assetConfig.fetch(assetConfigPda);
+      expect(assetConfig.oneSidedPolicy).to.deep.equal({ refund: {} });
+
+      await new Promise((resolve) => setTimeout(resolve, (TIMELOCK_SECS + 1) * 1000));
+      await executeChange(changeId);
+
+      assetConfig = await program.account.assetConfig.fetch(assetConfigPda);
+      expect(assetConfig.oneSidedPolicy).to.deep.equal({ rollover: {} });
+      expect(await provider.connection.getAccountInfo(pendingChangeAddress(changeId))).to.equal(null);
+    });
+
+    it("Cancels a queued change without applying it", async () => {
+      const changeId = await queuePolicyChange({ refund: {} });
+
+      await program.methods
+        .cancelChange(changeId)
+        .accounts({
+          pendingChange: pendingChangeAddress(changeId),
+          config: configPda,
+          proposer: admin.publicKey,
+          admin: admin.publicKey,
+        })
+        .signers([admin])
+        .rpc();
+
+      expect(await provider.connection.getAccountInfo(pendingChangeAddress(changeId))).to.equal(null);
+      const assetConfig = await program.account.assetConfig.fetch(assetConfigPda);
+      expect(assetConfig.oneSidedPolicy).to.deep.equal({ rollover: {} });
+    });
+  });
+
+  // Runs last: every earlier suite signs as the original admin
+  describe("admin handover", () => {
+    it("Moves the roles the outgoing admin still holds to the new admin", async () => {
+      const newAdmin = Keypair.generate();
+      const feeManager = Keypair.generate();
+
+      // Delegated roles stay with their holder
+      await program.methods
+        .setRoles(null, feeManager.publicKey, null)
+        .accounts({ config: configPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      await program.methods
+        .proposeAdmin(newAdmin.publicKey)
+        .accounts({ config: configPda, admin: admin.publicKey })
+        .signers([admin])
+        .rpc();
+
+      let config = await program.account.globalConfig.fetch(configPda);
+      expect(config.admin.toString()).to.equal(admin.publicKey.toString());
+      expect(config.pendingAdmin.toString()).to.equal(newAdmin.publicKey.toString());
+
+      // Only the proposed admin can accept
+      try {
+        await program.methods
+          .acceptAdmin()
+          .accounts({ config: configPda, pendingAdmin: user1.publicKey })
+          .signers([user1])
+          .rpc();
+        expect.fail("accept_admin accepted a signer other than the pending admin");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("NotPendingAdmin");
+      }
+
+      await program.methods
+        .acceptAdmin()
+        .accounts({ config: configPda, pendingAdmin: newAdmin.publicKey })
+        .signers([newAdmin])
+        .rpc();
+
+      config = await program.account.globalConfig.fetch(configPda);
+      expect(config.admin.toString()).to.equal(newAdmin.publicKey.toString());
+      expect(config.pendingAdmin.toString()).to.equal(PublicKey.default.toString());
+      expect(config.pauser.toString()).to.equal(newAdmin.publicKey.toString());
+      expect(config.assetManager.toString()).to.equal(newAdmin.publicKey.toString());
+      expect(config.feeManager.toString()).to.equal(feeManager.publicKey.toString());
+
+      // The old admin keeps no way to pause the protocol
+      try {
+        await program.methods
+          .pause()
+          .accounts({ config: configPda, authority: admin.publicKey })
+          .signers([admin])
+          .rpc();
+        expect.fail("the outgoing admin could still pause");
+      } catch (err) {
+        expect(err.error.errorCode.code).to.equal("Unauthorized");
+      }
+    });
+  });
 });
@@ -10,12 +10,13 @@ import * as dotenv from "dotenv";
 dotenv.config({ path: path.join(__dirname, "../env/.env") });
 
 /**
- * Create new epochs for all configured assets
+ * Create new epochs for all configured assets in one timeframe market
  */
 async function main() {
   // Load environment variables
   const RPC_HTTP = process.env.RPC_HTTP || "http://localhost:8899";
   const PYTH_FEEDS_JSON = process.env.PYTH_FEEDS_JSON;
+  const TIMEFRAME = process.env.TIMEFRAME || "5m";
 
   if (!PYTH_FEEDS_JSON) {
     throw new Error("PYTH_FEEDS_JSON environment variable is required");
@@ -44,16 +45,12 @@ async function main() {
 
   const program = new Program(idl, programId, provider) as Program<Betting>;
 
-  // Get config to read epoch length
   const [configPda] = PublicKey.findProgramAddressSync(
     [Buffer.from("config")],
     program.programId
   );
 
-  const config = await program.account.globalConfig.fetch(configPda);
-  const epochLengthSecs = config.epochLengthSecs;
-
-  console.log("Epoch length:", epochLengthSecs, "seconds");
+  console.log("Timeframe:", TIMEFRAME);
 
   // Create epoch for each asset
   for (const feed of pythFeeds) {
@@ -65,26 +62,41 @@ async function main() {
       program.programId
     );
 
-    // Calculate current epoch ID
-    const now = Math.floor(Date.now() / 1000);
-    const epochId = Math.floor(now / epochLengthSecs);
-
-    const [epochPda] = PublicKey.findProgramAddressSync(
-      [
-        Buffer.from("epoch"),
-        Buffer.from(symbol),
-        new anchor.BN(epochId).toArrayLike(Buffer, "be", 8),
-      ],
+    const [marketPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), Buffer.from(symbol), Buffer.from(TIMEFRAME)],
       program.programId
     );
 
     try {
+      // Calculate current epoch ID on the market's grid
+      const market = await program.account.market.fetch(marketPda);
+      const now = Math.floor(Date.now() / 1000);
+      const offset = now - market.gridOriginTs.toNumber();
+      const epochId =
+        offset >= 0
+          ? market.gridOriginEpochId.toNumber() + Math.floor(offset / market.epochLengthSecs)
+          : market.gridOriginEpochId.toNumber() -
+            Math.floor((-offset - 1) / market.prevEpochLengthSecs) -
+            1;
+
+      const [epochPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("epoch"),
+          Buffer.from(symbol),
+          Buffer.from(TIMEFRAME),
+          new anchor.BN(epochId).toArrayLike(Buffer, "be", 8),
+        ],
+        program.programId
+      );
+
       const tx = await program.methods
-        .createEpoch(symbol)
+        .createEpoch(symbol, TIMEFRAME)
         .accounts({
           epoch: epochPda,
+          market: marketPda,
           assetConfig: assetConfigPda,
           config: configPda,
+          pythPriceAccount: new PublicKey(feed.price),
           payer: wallet.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
@@ -9,6 +9,11 @@ import * as dotenv from "dotenv";
 // Load environment variables from /env/.env
 dotenv.config({ path: path.join(__dirname, "../env/.env") });
 
+// BPF upgradeable loader; owns each program's ProgramData account
+const BPF_LOADER_UPGRADEABLE_ID = new PublicKey(
+  "BPFLoaderUpgradeab1e11111111111111111111111"
+);
+
 /**
  * Deploy script
  *
@@ -21,6 +26,9 @@ async function main() {
   // Load environment variables
   const RPC_HTTP = process.env.RPC_HTTP || "http://localhost:8899";
   const TREASURY_PUBKEY = process.env.TREASURY_PUBKEY;
+  // Pyth oracle program (defaults to the devnet deployment)
+  const PYTH_PROGRAM_ID =
+    process.env.PYTH_PROGRAM_ID || "gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s";
   const SETTLE_TIP_LAMPORTS = parseInt(process.env.SETTLE_TIP_LAMPORTS || "10000");
   const EPOCH_LENGTH_SECS = parseInt(process.env.EPOCH_LENGTH_SECS || "300");
   const CUTOFF_SECS = parseInt(process.env.CUTOFF_SECS || "30");
@@ -62,6 +70,12 @@ async function main() {
 
   console.log("\nConfig PDA:", configPda.toString());
 
+  // Only the upgrade authority recorded in ProgramData may initialize
+  const [programData] = PublicKey.findProgramAddressSync(
+    [programId.toBuffer()],
+    BPF_LOADER_UPGRADEABLE_ID
+  );
+
   // Check if already initialized
   try {
     const config = await program.account.globalConfig.fetch(configPda);
@@ -77,6 +91,7 @@ async function main() {
       .initialize(
         wallet.publicKey,
         new PublicKey(TREASURY_PUBKEY),
+        new PublicKey(PYTH_PROGRAM_ID),
         FEE_BPS,
         new anchor.BN(SETTLE_TIP_LAMPORTS),
         CUTOFF_SECS,
@@ -85,6 +100,8 @@ async function main() {
       .accounts({
         config: configPda,
         payer: wallet.publicKey,
+        program: programId,
+        programData,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
       .rpc();
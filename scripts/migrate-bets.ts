@@ -0,0 +1,196 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { Betting } from "../contracts/betting/target/types/betting";
+import { PublicKey, Keypair } from "@solana/web3.js";
+import * as fs from "fs";
+import * as path from "path";
+import * as dotenv from "dotenv";
+
+// Load environment variables from /env/.env
+dotenv.config({ path: path.join(__dirname, "../env/.env") });
+
+// Account sizes written before timeframe markets
+const LEGACY_CONFIG_LEN = 92;
+const LEGACY_ASSET_CONFIG_LEN = 101;
+const LEGACY_EPOCH_LEN = 125;
+const LEGACY_USER_BET_LEN = 80;
+
+/**
+ * Migrate config, asset, epoch and bet accounts from before timeframe markets to the current layouts
+ *
+ * Migrated accounts keep their addresses under an empty timeframe, so claims and refunds pass ""
+ * The config step needs the admin wallet; the rest is permissionless - the wallet pays the few
+ * lamports of extra rent per account
+ */
+async function main() {
+  // Load environment variables
+  const RPC_HTTP = process.env.RPC_HTTP || "http://localhost:8899";
+  const PYTH_PROGRAM_ID =
+    process.env.PYTH_PROGRAM_ID || "gSbePebfvPy7tRqimPoVecS2UsBvYv46ynrzWocc92s";
+
+  // Setup provider
+  const connection = new anchor.web3.Connection(RPC_HTTP, "confirmed");
+  const walletPath = path.join(process.env.HOME || "", ".config/solana/id.json");
+  const walletKeypair = Keypair.fromSecretKey(
+    Buffer.from(JSON.parse(fs.readFileSync(walletPath, "utf-8")))
+  );
+  const wallet = new anchor.Wallet(walletKeypair);
+  const provider = new anchor.AnchorProvider(connection, wallet, {
+    commitment: "confirmed",
+  });
+
+  anchor.setProvider(provider);
+
+  // Load program
+  const idlPath = path.join(__dirname, "../contracts/betting/target/idl/betting.json");
+  const idl = JSON.parse(fs.readFileSync(idlPath, "utf-8"));
+  const programId = new PublicKey(idl.address || idl.metadata.address);
+
+  const program = new Program(idl, programId, provider) as Program<Betting>;
+
+  // Legacy accounts share the current discriminators but have the old sizes
+  const findLegacy = (accountName: string, dataSize: number) =>
+    connection.getProgramAccounts(program.programId, {
+      filters: [
+        { dataSize },
+        {
+          memcmp: {
+            offset: 0,
+            bytes: anchor.utils.bytes.bs58.encode(
+              anchor.BorshAccountsCoder.accountDiscriminator(accountName)
+            ),
+          },
+        },
+      ],
+    });
+
+  // Config first: epochs take their payout fee from its legacy_fee_bps snapshot
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  const configInfo = await connection.getAccountInfo(configPda);
+  if (configInfo?.data.length === LEGACY_CONFIG_LEN) {
+    console.log("🔧 Migrating config...");
+    const tx = await program.methods
+      .migrateConfig(new PublicKey(PYTH_PROGRAM_ID))
+      .accounts({
+        config: configPda,
+        admin: wallet.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    console.log("✅ Migrated");
+    console.log("Transaction:", tx);
+  }
+
+  const legacyAssets = await findLegacy("AssetConfig", LEGACY_ASSET_CONFIG_LEN);
+  console.log("\nLegacy assets found:", legacyAssets.length);
+
+  for (const { pubkey, account } of legacyAssets) {
+    // asset_symbol (string) | pyth_price_account (32) | usdc_mint (32)
+    const data = account.data;
+    const symbolLen = data.readUInt32LE(8);
+    const assetSymbol = data.subarray(12, 12 + symbolLen).toString();
+    const usdcMint = new PublicKey(data.subarray(44 + symbolLen, 76 + symbolLen));
+
+    // Keep the old vault if it was ever created
+    const [vaultPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), usdcMint.toBuffer(), Buffer.from(assetSymbol)],
+      program.programId
+    );
+    const vault = (await connection.getAccountInfo(vaultPda)) ? vaultPda : null;
+
+    console.log(`\n🔧 Migrating asset ${pubkey.toString()} (${assetSymbol})...`);
+
+    try {
+      const tx = await program.methods
+        .migrateAsset(assetSymbol)
+        .accounts({
+          assetConfig: pubkey,
+          vault,
+          payer: wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("✅ Migrated");
+      console.log("Transaction:", tx);
+    } catch (err) {
+      console.error(`❌ Failed to migrate ${pubkey.toString()}:`, err.message);
+    }
+  }
+
+  // Epochs next, so refunds and claims work as soon as the bets are migrated
+  const legacyEpochs = await findLegacy("Epoch", LEGACY_EPOCH_LEN);
+  console.log("\nLegacy epochs found:", legacyEpochs.length);
+
+  for (const { pubkey, account } of legacyEpochs) {
+    // asset_symbol (string) | epoch_id (u64 LE)
+    const data = account.data;
+    const symbolLen = data.readUInt32LE(8);
+    const assetSymbol = data.subarray(12, 12 + symbolLen).toString();
+    const epochId = new anchor.BN(data.subarray(12 + symbolLen, 20 + symbolLen), "le");
+
+    console.log(`\n🔧 Migrating epoch ${pubkey.toString()} (${assetSymbol} #${epochId})...`);
+
+    try {
+      const tx = await program.methods
+        .migrateEpoch(assetSymbol, epochId)
+        .accounts({
+          epoch: pubkey,
+          payer: wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("✅ Migrated");
+      console.log("Transaction:", tx);
+    } catch (err) {
+      console.error(`❌ Failed to migrate ${pubkey.toString()}:`, err.message);
+    }
+  }
+
+  const legacyBets = await findLegacy("UserBet", LEGACY_USER_BET_LEN);
+  console.log("\nLegacy bets found:", legacyBets.length);
+
+  for (const { pubkey, account } of legacyBets) {
+    // user (32) | asset_symbol (string) | epoch_id (u64 LE)
+    const data = account.data;
+    let offset = 8;
+    const user = new PublicKey(data.subarray(offset, offset + 32));
+    offset += 32;
+    const symbolLen = data.readUInt32LE(offset);
+    const assetSymbol = data.subarray(offset + 4, offset + 4 + symbolLen).toString();
+    offset += 4 + symbolLen;
+    const epochId = new anchor.BN(data.subarray(offset, offset + 8), "le");
+
+    console.log(`\n🔧 Migrating bet ${pubkey.toString()} (${assetSymbol} #${epochId})...`);
+
+    try {
+      const tx = await program.methods
+        .migrateBet(assetSymbol, epochId)
+        .accounts({
+          userBet: pubkey,
+          user,
+          payer: wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      console.log("✅ Migrated");
+      console.log("Transaction:", tx);
+    } catch (err) {
+      console.error(`❌ Failed to migrate ${pubkey.toString()}:`, err.message);
+    }
+  }
+
+  console.log("\n🎉 Migration complete!");
+}
+
+main()
+  .then(() => process.exit(0))
+  .catch((err) => {
+    console.error(err);
+    process.exit(1);
+  });
@@ -2,6 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { Betting } from "../contracts/betting/target/types/betting";
 import { PublicKey, Keypair } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import * as fs from "fs";
 import * as path from "path";
 import * as dotenv from "dotenv";
@@ -19,6 +20,7 @@ async function main() {
   const RPC_HTTP = process.env.RPC_HTTP || "http://localhost:8899";
   const USDC_MINT = process.env.USDC_MINT;
   const PYTH_FEEDS_JSON = process.env.PYTH_FEEDS_JSON;
+  const TIMEFRAME = process.env.TIMEFRAME || "5m";
 
   if (!USDC_MINT) {
     throw new Error("USDC_MINT environment variable is required");
@@ -78,7 +80,7 @@ async function main() {
         .accounts({
           assetConfig: assetConfigPda,
           config: configPda,
-          admin: wallet.publicKey,
+          authority: wallet.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
         })
         .rpc();
@@ -86,6 +88,52 @@ async function main() {
       console.log(`✅ ${symbol} configured`);
       console.log("Asset Config PDA:", assetConfigPda.toString());
       console.log("Transaction:", tx);
+
+      // Create the USDC vault once per asset and mint
+      const [vaultPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("vault"),
+          new PublicKey(USDC_MINT).toBuffer(),
+          Buffer.from(symbol),
+        ],
+        program.programId
+      );
+      if (!(await connection.getAccountInfo(vaultPda))) {
+        const vaultTx = await program.methods
+          .initVault(symbol)
+          .accounts({
+            vault: vaultPda,
+            usdcMint: new PublicKey(USDC_MINT),
+            assetConfig: assetConfigPda,
+            config: configPda,
+            authority: wallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        console.log("Vault PDA:", vaultPda.toString());
+        console.log("Transaction:", vaultTx);
+      }
+
+      // Open the default timeframe market using the global epoch length and cutoff
+      const [marketPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("market"), Buffer.from(symbol), Buffer.from(TIMEFRAME)],
+        program.programId
+      );
+      if (!(await connection.getAccountInfo(marketPda))) {
+        const marketTx = await program.methods
+          .createMarket(symbol, TIMEFRAME, null, null, null)
+          .accounts({
+            market: marketPda,
+            assetConfig: assetConfigPda,
+            config: configPda,
+            authority: wallet.publicKey,
+            systemProgram: anchor.web3.SystemProgram.programId,
+          })
+          .rpc();
+        console.log(`Market PDA (${TIMEFRAME}):`, marketPda.toString());
+        console.log("Transaction:", marketTx);
+      }
     } catch (err) {
       console.error(`❌ Failed to configure ${symbol}:`, err);
     }
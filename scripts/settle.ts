@@ -18,14 +18,15 @@ dotenv.config({ path: path.join(__dirname, "../env/.env") });
 async function main() {
   const args = process.argv.slice(2);
 
-  if (args.length < 2) {
-    console.log("Usage: ts-node settle.ts <ASSET_SYMBOL> <EPOCH_ID>");
-    console.log("Example: ts-node settle.ts BTCUSD 12345");
+  if (args.length < 3) {
+    console.log("Usage: ts-node settle.ts <ASSET_SYMBOL> <TIMEFRAME> <EPOCH_ID>");
+    console.log("Example: ts-node settle.ts BTCUSD 5m 12345");
     process.exit(1);
   }
 
   const assetSymbol = args[0];
-  const epochId = parseInt(args[1]);
+  const timeframe = args[1];
+  const epochId = parseInt(args[2]);
 
   // Load environment variables
   const RPC_HTTP = process.env.RPC_HTTP || "http://localhost:8899";
@@ -66,10 +67,16 @@ async function main() {
     program.programId
   );
 
+  const [marketPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("market"), Buffer.from(assetSymbol), Buffer.from(timeframe)],
+    program.programId
+  );
+
   const [epochPda] = PublicKey.findProgramAddressSync(
     [
       Buffer.from("epoch"),
       Buffer.from(assetSymbol),
+      Buffer.from(timeframe),
       new anchor.BN(epochId).toArrayLike(Buffer, "be", 8),
     ],
     program.programId
@@ -90,6 +97,7 @@ async function main() {
   const config = await program.account.globalConfig.fetch(configPda);
 
   console.log("Asset:", assetSymbol);
+  console.log("Timeframe:", timeframe);
   console.log("Epoch ID:", epochId);
   console.log("Status:", epoch.status);
   console.log("End time:", new Date(epoch.endTs.toNumber() * 1000).toISOString());
@@ -116,18 +124,33 @@ async function main() {
     owner: config.treasury,
   });
 
+  // Keeper share of the fee goes to the caller's ATA
+  const callerAta = anchor.utils.token.associatedAddress({
+    mint: new PublicKey(USDC_MINT),
+    owner: wallet.publicKey,
+  });
+
+  // Settle tips are paid from the tip jar (funded through fund_tips)
+  const [tipJarPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("tip_jar")],
+    program.programId
+  );
+
   console.log("\n🔧 Settling epoch...");
 
   try {
     const tx = await program.methods
-      .closeEpoch(assetSymbol, new anchor.BN(epochId))
+      .closeEpoch(assetSymbol, timeframe, new anchor.BN(epochId))
       .accounts({
         epoch: epochPda,
+        market: marketPda,
         assetConfig: assetConfigPda,
         config: configPda,
         vault: vaultPda,
         treasuryAta: treasuryAta,
         pythPriceAccount: assetConfig.pythPriceAccount,
+        tipJar: tipJarPda,
+        callerAta,
         caller: wallet.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,